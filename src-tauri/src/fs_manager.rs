@@ -2,14 +2,24 @@ use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::io::Write;
+use crate::safe_path;
 
 pub fn get_projects_dir() -> PathBuf {
     let home = env::var("HOME").expect("HOME not set");
     Path::new(&home).join("RobloxProjects")
 }
 
+/// `name` and `relative_path` come from clients: both must stay inside the projects folder.
+fn checked_path(project_name: &str, relative_path: &str) -> std::io::Result<PathBuf> {
+    let invalid = |e: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, e);
+    let project_dir = safe_path::resolve(&get_projects_dir(), project_name).map_err(invalid)?;
+    safe_path::resolve(&project_dir, relative_path).map_err(invalid)
+}
+
 pub fn create_project(name: &str) -> std::io::Result<PathBuf> {
-    let project_dir = get_projects_dir().join(name);
+    fs::create_dir_all(get_projects_dir())?;
+    let project_dir = safe_path::resolve(&get_projects_dir(), name)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
     
     // WIPEOUT: Remove existing directory to prevent stale files
     if project_dir.exists() {
@@ -27,8 +37,7 @@ pub fn create_project(name: &str) -> std::io::Result<PathBuf> {
 }
 
 pub fn write_file(project_name: &str, relative_path: &str, content: &str) -> std::io::Result<()> {
-    let project_dir = get_projects_dir().join(project_name);
-    let full_path = project_dir.join(relative_path);
+    let full_path = checked_path(project_name, relative_path)?;
 
     if let Some(parent) = full_path.parent() {
         fs::create_dir_all(parent)?;
@@ -41,8 +50,7 @@ pub fn write_file(project_name: &str, relative_path: &str, content: &str) -> std
 }
 
 pub fn delete_file(project_name: &str, relative_path: &str) -> std::io::Result<()> {
-    let project_dir = get_projects_dir().join(project_name);
-    let full_path = project_dir.join(relative_path);
+    let full_path = checked_path(project_name, relative_path)?;
     if full_path.exists() {
         fs::remove_file(full_path)?;
     }
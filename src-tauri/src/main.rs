@@ -6,10 +6,24 @@ use std::sync::{Arc, Mutex};
 mod server_http;
 mod server_ws;
 mod fs_manager;
+mod safe_path;
 mod watcher;
 mod plugin_manager;
 mod robezy;
 
+use crate::robezy::session::SessionManager;
+
+/// Saves the RoBezy sessions one last time, so nothing queued since the last save is lost on quit.
+fn save_sessions(app: &tauri::AppHandle) {
+    if let Some(manager) = app.try_state::<Arc<Mutex<SessionManager>>>() {
+        // Snapshot under the lock, write after releasing it
+        let pending = manager.lock().ok().and_then(|mut manager| manager.state_snapshot());
+        if let Some(Err(e)) = pending.map(|pending| pending.write()) {
+            eprintln!("RoBezy: {}", e);
+        }
+    }
+}
+
 fn main() {
     let quit = CustomMenuItem::new("quit".to_string(), "Quit");
     let open = CustomMenuItem::new("open".to_string(), "Open Dashboard");
@@ -35,12 +49,12 @@ fn main() {
         .setup(|app| {
             // Create broadcast channel for logs
             use crate::server_ws::InternalBroadcast;
-            use crate::server_ws::RobloxCommand;
+            use crate::server_ws::CommandQueue;
             
             let (log_tx, _) = tokio::sync::broadcast::channel::<InternalBroadcast>(100);
             
             // Shared Command Queue
-            let command_queue = Arc::new(Mutex::new(Vec::<RobloxCommand>::new()));
+            let command_queue = CommandQueue::default();
             
             tauri::async_runtime::spawn(server_http::start_server(log_tx.clone(), command_queue.clone()));
 
@@ -52,8 +66,8 @@ fn main() {
             });
 
             // --- RoBezy (Studio-First) Server (3032) ---
-            use crate::robezy::session::SessionManager;
-            let session_manager = Arc::new(Mutex::new(SessionManager::new()));
+            let session_manager = Arc::new(Mutex::new(SessionManager::new(log_tx.clone())));
+            app.manage(session_manager.clone()); // For the save on quit
             tauri::async_runtime::spawn(robezy::server::start_robezy_server(session_manager.clone(), 3032));
             
              // --- Event Server (3031) ---
@@ -108,6 +122,7 @@ fn main() {
             SystemTrayEvent::MenuItemClick { id, .. } => {
                 match id.as_str() {
                     "quit" => {
+                        save_sessions(app);
                         std::process::exit(0);
                     }
                     "open" => {
@@ -127,6 +142,11 @@ fn main() {
             }
             _ => {}
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                save_sessions(app);
+            }
+        });
 }
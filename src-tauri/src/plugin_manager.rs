@@ -317,9 +317,9 @@ print("WorkspaceListener Plugin Active (Edit Mode Only)")
 			<string name="ScriptGuid">{B2C3D4E5-F678-9012-3456-789ABCDEF012}</string>
 			<ProtectedString name="Source"><![CDATA[local HttpService = game:GetService("HttpService")
 local CollectionService = game:GetService("CollectionService")
-local COMMAND_URL = "http://127.0.0.1:3030/roblox/commands"
+local COMMAND_URL = "http://127.0.0.1:3030/roblox/commands?wait=25" -- Long-poll: returns as soon as a command is queued
 local EXECUTION_URL = "http://127.0.0.1:3030/roblox/execution"
-local POLL_INTERVAL = 0.5
+local POLL_INTERVAL = 0.5 -- Back-off when the app is not running
 
 -- Helper to find instance by path string
 local function findInstanceByPath(path)
@@ -393,7 +393,9 @@ local function pollCommands()
     local success, response = pcall(function()
         return HttpService:GetAsync(COMMAND_URL, false)
     end)
-    if success then
+    if not success then
+        task.wait(POLL_INTERVAL)
+    else
         local valid, commands = pcall(function() return HttpService:JSONDecode(response) end)
         if valid and type(commands) == "table" then
             for _, cmd in ipairs(commands) do
@@ -405,7 +407,6 @@ end
 
 while true do
     pollCommands()
-    task.wait(POLL_INTERVAL)
 end
 ]]></ProtectedString>
 		</Properties>
@@ -431,6 +432,7 @@ end
 			<ProtectedString name="Source"><![CDATA[local HttpService = game:GetService("HttpService")
 local RunService = game:GetService("RunService")
 local CoreGui = game:GetService("CoreGui")
+local CollectionService = game:GetService("CollectionService")
 
 local ROBEZY_URL = "http://localhost:3032/robezy"
 local CONNECT_URL = ROBEZY_URL .. "/connect"
@@ -439,6 +441,7 @@ local DISCONNECT_URL = ROBEZY_URL .. "/disconnect"
 local HEARTBEAT_URL = ROBEZY_URL .. "/heartbeat"
 local CHECK_URL = ROBEZY_URL .. "/sessions" -- Using this as heartbeat for now
 local POLL_URL = ROBEZY_URL .. "/poll_changes" -- POLL INBOUND from FS
+local ACK_URL = ROBEZY_URL .. "/ack" -- Confirms applied changes (unacked ones are redelivered)
 
 -- === THEME CONFIG ===
 local Colors = {
@@ -572,7 +575,8 @@ local State = {
     Connected = false,
     ApplyingChanges = false,
     SessionId = "",
-    ProjectId = ""
+    ProjectId = "",
+    PollCursor = 0 -- Highest change seq received this session
 }
 
 -- === FUNCTIONS ===
@@ -580,13 +584,241 @@ local State = {
 -- DIRTY STATE & POLL
 local DirtyScripts = {} 
 local LastWrittenContent = {}
+local KnownPaths = {} -- Script -> last synced "Service.Folder.Name" (detects rename / move / delete)
+local MovedScripts = {}
 
 local function countDirty()
     local c = 0
     for _, _ in pairs(DirtyScripts) do c = c + 1 end
+    for _, _ in pairs(MovedScripts) do c = c + 1 end
     return c
 end
 
+local ID_ATTRIBUTE = "RoBezyId" -- Persistent id the backend keys files by (Debug IDs change every Studio session)
+local InstancesByGuid = {} -- RoBezyId -> instance, resolves "Name~2" files to the right sibling
+
+-- The instance's persistent id, created on first use. A copy (Ctrl+D) carries its original's id,
+-- so a second live instance with the same id gets a new one.
+local function getGuid(inst)
+    local id = inst:GetAttribute(ID_ATTRIBUTE)
+    local owner = type(id) == "string" and InstancesByGuid[id]
+    if type(id) ~= "string" or (owner and owner ~= inst and owner:IsDescendantOf(game)) then
+        id = HttpService:GenerateGUID(false)
+        pcall(function() inst:SetAttribute(ID_ATTRIBUTE, id) end)
+    end
+    InstancesByGuid[id] = inst
+    return id
+end
+
+-- 1 for the first child of its parent with this Name and ClassName, 2 for the next, ...
+local function siblingIndex(inst)
+    local parent = inst.Parent
+    if not parent or parent == game then return 1 end
+    local n = 0
+    for _, child in ipairs(parent:GetChildren()) do
+        if child.Name == inst.Name and child.ClassName == inst.ClassName then
+            n = n + 1
+            if child == inst then return n end
+        end
+    end
+    return 1
+end
+
+-- Path segment for an instance: "Handler", then "Handler~2", "Handler~3" for same-named siblings
+-- of the same class (the backend's file names use the same numbers)
+local function segmentName(inst)
+    local n = siblingIndex(inst)
+    if n > 1 then return inst.Name .. "~" .. n end
+    return inst.Name
+end
+
+local function getInstancePath(instance, separator)
+    separator = separator or "."
+    local path = segmentName(instance)
+    local callback = instance.Parent
+    while callback and callback ~= game do
+        path = segmentName(callback) .. separator .. path
+        callback = callback.Parent
+    end
+    return path
+end
+
+-- === TEXT INSTANCES (.txt -> StringValue, .csv -> LocalizationTable) ===
+local function csvField(value)
+    value = tostring(value or "")
+    if string.find(value, '[,"\r\n]') then
+        return '"' .. (string.gsub(value, '"', '""')) .. '"'
+    end
+    return value
+end
+
+-- Entries -> CSV in Studio's export layout (Key, Source, Context, Example, locales...)
+local function encodeCsv(tbl)
+    local entries = tbl:GetEntries()
+    local locales, seen = {}, {}
+    for _, entry in ipairs(entries) do
+        for locale, _ in pairs(entry.Values or {}) do
+            if not seen[locale] then
+                seen[locale] = true
+                table.insert(locales, locale)
+            end
+        end
+    end
+    table.sort(locales)
+    local header = {"Key", "Source", "Context", "Example"}
+    for _, locale in ipairs(locales) do table.insert(header, locale) end
+    local lines = {table.concat(header, ",")}
+    for _, entry in ipairs(entries) do
+        local row = {csvField(entry.Key), csvField(entry.Source), csvField(entry.Context), csvField(entry.Example)}
+        for _, locale in ipairs(locales) do table.insert(row, csvField((entry.Values or {})[locale])) end
+        table.insert(lines, table.concat(row, ","))
+    end
+    return table.concat(lines, "\n") .. "\n"
+end
+
+-- CSV -> rows of fields. The backend rejects malformed files, so this only reads valid CSV.
+local function parseCsv(text)
+    local rows, row, field = {}, {}, {}
+    local quoted = false
+    local i = 1
+    while i <= #text do
+        local c = string.sub(text, i, i)
+        if quoted then
+            if c == '"' and string.sub(text, i + 1, i + 1) == '"' then
+                table.insert(field, '"')
+                i = i + 1
+            elseif c == '"' then
+                quoted = false
+            else
+                table.insert(field, c)
+            end
+        elseif c == '"' then
+            quoted = true
+        elseif c == "," then
+            table.insert(row, table.concat(field))
+            field = {}
+        elseif c == "\n" then
+            table.insert(row, table.concat(field))
+            table.insert(rows, row)
+            row, field = {}, {}
+        elseif c ~= "\r" then
+            table.insert(field, c)
+        end
+        i = i + 1
+    end
+    if #field > 0 or #row > 0 then
+        table.insert(row, table.concat(field))
+        table.insert(rows, row)
+    end
+    return rows
+end
+
+local function decodeCsv(text)
+    local rows = parseCsv((string.gsub(text, "^\239\187\191", "")))
+    local header = table.remove(rows, 1) or {}
+    local entries = {}
+    for _, row in ipairs(rows) do
+        if not (#row == 1 and row[1] == "") then
+            local entry = {Key = "", Source = "", Context = "", Example = "", Values = {}}
+            for col, name in ipairs(header) do
+                local value = row[col] or ""
+                if name == "Key" or name == "Source" or name == "Context" or name == "Example" then
+                    entry[name] = value
+                elseif value ~= "" then
+                    entry.Values[name] = value
+                end
+            end
+            table.insert(entries, entry)
+        end
+    end
+    return entries
+end
+
+local function isTextInstance(inst)
+    return inst:IsA("StringValue") or inst:IsA("LocalizationTable")
+end
+
+-- Instances that are synced as a file of their own (RoBezy's own config value stays out)
+local function isSynced(inst)
+    if inst.Name == "RoBezyConfig" or inst.Name == "RoBezy_Snapshot_Link" then return false end
+    return inst:IsA("LuaSourceContainer") or isTextInstance(inst)
+end
+
+-- What the instance's file holds
+local function readContent(inst)
+    if inst:IsA("LuaSourceContainer") then return inst.Source
+    elseif inst:IsA("StringValue") then return inst.Value
+    elseif inst:IsA("LocalizationTable") then return encodeCsv(inst)
+    end
+    return nil
+end
+
+local function writeContent(inst, content)
+    if inst:IsA("LuaSourceContainer") then inst.Source = content
+    elseif inst:IsA("StringValue") then inst.Value = content
+    elseif inst:IsA("LocalizationTable") then inst:SetEntries(decodeCsv(content))
+    end
+    LastWrittenContent[inst] = readContent(inst)
+end
+
+-- Remember where a synced instance (and any inside it) currently lives
+local function rememberPaths(inst)
+    if isSynced(inst) then
+        KnownPaths[inst] = getInstancePath(inst)
+        MovedScripts[inst] = nil
+    end
+    for _, desc in ipairs(inst:GetDescendants()) do
+        if isSynced(desc) then
+            KnownPaths[desc] = getInstancePath(desc)
+            MovedScripts[desc] = nil
+        end
+    end
+end
+
+-- Roblox value -> JSON, using Rojo's explicit form ({"Vector3": [1, 2, 3]}) for non-primitive types
+local function encodeValue(v)
+    local t = typeof(v)
+    if t == "Vector3" then return {Vector3 = {v.X, v.Y, v.Z}}
+    elseif t == "Vector2" then return {Vector2 = {v.X, v.Y}}
+    elseif t == "Color3" then return {Color3 = {v.R, v.G, v.B}}
+    elseif t == "UDim" then return {UDim = {v.Scale, v.Offset}}
+    elseif t == "UDim2" then return {UDim2 = {{v.X.Scale, v.X.Offset}, {v.Y.Scale, v.Y.Offset}}}
+    elseif t == "BrickColor" then return {BrickColor = v.Number}
+    elseif t == "NumberRange" then return {NumberRange = {v.Min, v.Max}}
+    elseif t == "CFrame" then
+        local x, y, z, r00, r01, r02, r10, r11, r12, r20, r21, r22 = v:GetComponents()
+        return {CFrame = {position = {x, y, z}, orientation = {{r00, r01, r02}, {r10, r11, r12}, {r20, r21, r22}}}}
+    elseif t == "EnumItem" then return {Enum = v.Name}
+    end
+    return v -- string, number, boolean
+end
+
+-- Attributes, tags, Enabled and RunContext stored in the script's sidecar .meta.json
+local function withMeta(change, inst)
+    local attributes = {}
+    for name, value in pairs(inst:GetAttributes()) do
+        if name ~= ID_ATTRIBUTE then attributes[name] = encodeValue(value) end
+    end
+    change.attributes = attributes
+    change.tags = CollectionService:GetTags(inst)
+    if inst:IsA("BaseScript") then change.enabled = inst.Enabled end
+    if inst.ClassName == "Script" then change.run_context = inst.RunContext.Name end
+    return change
+end
+
+-- True once the backend accepted the changes
+local function postChanges(changes)
+    if #changes == 0 then return true end
+    local payload = {
+        session_id = State.SessionId,
+        changes = changes
+    }
+    local ok, resp = pcall(function()
+        return HttpService:PostAsync(ROBEZY_URL .. "/sync", HttpService:JSONEncode(payload), Enum.HttpContentType.ApplicationJson, false)
+    end)
+    return ok and not string.find(resp, '"error', 1, true)
+end
+
 local function updateUI()
     -- Guard against missing UI elements if setup failed
     if not connectBtn then return end
@@ -642,41 +874,60 @@ local function syncLogic(scriptInstance)
     if not State.Connected then return end
     if State.ApplyingChanges then return end
     
-    local SYNC_URL = ROBEZY_URL .. "/sync"
-    
-     local function getPath(instance)
-        local path = instance.Name
-        local callback = instance.Parent
-        while callback and callback ~= game do
-            path = callback.Name .. "." .. path
-            callback = callback.Parent
-        end
-        return path
-    end
+    if not scriptInstance:IsDescendantOf(game) then return end
     
-    local guid = scriptInstance:GetDebugId()
+    local guid = getGuid(scriptInstance)
     local className = scriptInstance.ClassName
-    local payload = {
-        session_id = State.SessionId,
-        changes = {{
-            change_type = "write",
-            path = getPath(scriptInstance),
-            content = scriptInstance.Source,
-            is_script = true,
-            guid = guid,
-            class_name = className
-        }}
-    }
-    pcall(function()
-        HttpService:PostAsync(SYNC_URL, HttpService:JSONEncode(payload), Enum.HttpContentType.ApplicationJson, false)
-    end)
+    local content = readContent(scriptInstance)
+    postChanges({withMeta({
+        change_type = "write",
+        path = getInstancePath(scriptInstance),
+        content = content,
+        is_script = scriptInstance:IsA("LuaSourceContainer"),
+        guid = guid,
+        class_name = className
+    }, scriptInstance)})
     
      -- Mark clean + updating debounce logic to avoid loopback
     DirtyScripts[scriptInstance] = nil
-    LastWrittenContent[scriptInstance] = scriptInstance.Source 
+    LastWrittenContent[scriptInstance] = content 
 end
 
 
+-- Compare every known script against where it was last synced.
+-- Catches deletes, renames, reparents and renamed ancestor folders.
+local function collectStructuralChanges()
+    local changes = {}
+    for inst, oldPath in pairs(KnownPaths) do
+        local base = {
+            guid = getGuid(inst),
+            class_name = inst.ClassName,
+            is_script = inst:IsA("LuaSourceContainer")
+        }
+        if not inst:IsDescendantOf(game) then
+            base.change_type = "delete"
+            base.path = oldPath
+            table.insert(changes, base)
+            KnownPaths[inst] = nil
+            DirtyScripts[inst] = nil
+        else
+            local newPath = getInstancePath(inst)
+            if newPath ~= oldPath then
+                local oldParent = string.match(oldPath, "^(.*)%.[^%.]+$")
+                local newParent = string.match(newPath, "^(.*)%.[^%.]+$")
+                base.change_type = (oldParent == newParent) and "rename" or "move"
+                base.path = newPath
+                base.old_path = oldPath
+                base.content = readContent(inst)
+                table.insert(changes, base)
+                KnownPaths[inst] = newPath
+            end
+        end
+    end
+    MovedScripts = {}
+    return changes
+end
+
 local function forceSyncAll()
     if not State.Connected then return end
     
@@ -689,6 +940,9 @@ local function forceSyncAll()
     end
     task.wait(0.1)
     
+    -- Structure first, so content writes land on the new paths
+    postChanges(collectStructuralChanges())
+    
     for scriptInst, _ in pairs(DirtyScripts) do
         syncLogic(scriptInst)
     end
@@ -697,7 +951,161 @@ local function forceSyncAll()
     updateUI()
 end
 
-local function ensureInstance(fsPath, leafClass)
+local function stripExtension(nameStr)
+    nameStr = string.gsub(nameStr, "%.server%.luau?$", "")
+    nameStr = string.gsub(nameStr, "%.client%.luau?$", "")
+    nameStr = string.gsub(nameStr, "%.luau?$", "")
+    nameStr = string.gsub(nameStr, "%.txt$", "")
+    nameStr = string.gsub(nameStr, "%.csv$", "")
+    nameStr = string.gsub(nameStr, "%.model%.json$", "")
+    nameStr = string.gsub(nameStr, "%.meta%.json$", "")
+    nameStr = string.gsub(nameStr, "%.json$", "")
+    return nameStr
+end
+
+local function isScriptClass(className)
+    return className == "Script" or className == "LocalScript" or className == "ModuleScript"
+end
+
+-- Classes whose file is their content (scripts, StringValue, LocalizationTable)
+local function isContentClass(className)
+    return isScriptClass(className) or className == "StringValue" or className == "LocalizationTable"
+end
+
+-- "Handler~2" -> "Handler", 2
+local function splitDuplicateSuffix(nameStr)
+    local base, n = string.match(nameStr, "^(.-)~(%d+)$")
+    if base then return base, tonumber(n) end
+    return nameStr, 1
+end
+
+-- The child a "Name~N" path segment stands for: the Nth child with that name and `className`
+-- (see `segmentName`). Without a class (folders in a path), the first child that is the Nth of its class.
+local function findSibling(parent, nameStr, className)
+    local name, index = splitDuplicateSuffix(nameStr)
+    local seen = {}
+    for _, child in ipairs(parent:GetChildren()) do
+        if child.Name == name and (not className or child.ClassName == className) then
+            seen[child.ClassName] = (seen[child.ClassName] or 0) + 1
+            if seen[child.ClassName] == index then return child end
+        end
+    end
+    return nil
+end
+
+-- Class to look a path segment up with: the leaf's own class, or a script's for the folder of its init file
+local function segmentClass(segments, i, leafClass)
+    if i == #segments then
+        return isContentClass(leafClass) and leafClass or nil
+    end
+    if i == #segments - 1 and stripExtension(segments[#segments]) == "init" and isScriptClass(leafClass) then
+        return leafClass
+    end
+    return nil
+end
+
+local function byGuid(guid)
+    local inst = guid and InstancesByGuid[guid]
+    if inst and inst:IsDescendantOf(game) then return inst end
+    return nil
+end
+
+-- Swap an instance for one of another class, keeping name, parent, source and children
+local function replaceClass(inst, className)
+    local ok, replacement = pcall(function() return Instance.new(className) end)
+    if not ok then return inst end
+    replacement.Name = inst.Name
+    local id = inst:GetAttribute(ID_ATTRIBUTE)
+    if id then
+        replacement:SetAttribute(ID_ATTRIBUTE, id)
+        InstancesByGuid[id] = replacement
+    end
+    if inst:IsA("LuaSourceContainer") and replacement:IsA("LuaSourceContainer") then
+        replacement.Source = inst.Source
+        LastWrittenContent[replacement] = inst.Source
+    end
+    for _, child in ipairs(inst:GetChildren()) do
+        child.Parent = replacement
+    end
+    replacement.Parent = inst.Parent
+    DirtyScripts[inst] = nil
+    LastWrittenContent[inst] = nil
+    KnownPaths[inst] = nil
+    MovedScripts[inst] = nil
+    inst:Destroy()
+    return replacement
+end
+
+-- Resolve a FS path to an existing instance WITHOUT creating anything
+local function findInstance(fsPath, guid, className)
+    local known = byGuid(guid)
+    if known then return known end
+    fsPath = string.gsub(fsPath, "\\", "/")
+    local segments = string.split(fsPath, "/")
+    if #segments == 0 then return nil end
+    local success, current = pcall(function() return game:GetService(segments[1]) end)
+    if not success or not current then return nil end
+    for i = 2, #segments do
+        local nameStr = segments[i]
+        if i == #segments then
+            nameStr = stripExtension(nameStr)
+            if nameStr == "init" then return current end
+        end
+        local segClass = segmentClass(segments, i, className)
+        current = findSibling(current, nameStr, segClass) or (segClass and i < #segments and findSibling(current, nameStr, "Folder"))
+        if not current then return nil end
+    end
+    return current
+end
+
+local function forgetInstance(inst)
+    local id = inst:GetAttribute(ID_ATTRIBUTE)
+    if id and InstancesByGuid[id] == inst then InstancesByGuid[id] = nil end
+    DirtyScripts[inst] = nil
+    LastWrittenContent[inst] = nil
+    KnownPaths[inst] = nil
+    MovedScripts[inst] = nil
+    for _, desc in ipairs(inst:GetDescendants()) do
+        DirtyScripts[desc] = nil
+        LastWrittenContent[desc] = nil
+        KnownPaths[desc] = nil
+        MovedScripts[desc] = nil
+    end
+end
+
+local function deleteInstance(fsPath, isFolder, guid, className)
+    local inst = findInstance(fsPath, guid, className)
+    if not inst then return end
+    -- Never destroy services
+    if inst.Parent == game then
+        warn("RoBezy: Refusing to delete service " .. inst.Name)
+        return
+    end
+    local leaf = string.match(string.gsub(fsPath, "\\", "/"), "([^/]+)$") or ""
+    if not isFolder and stripExtension(leaf) == "init" and inst:IsA("LuaSourceContainer") then
+        -- Only the init file went away: the folder (and the scripts in it) stays
+        print("RoBezy: Turning " .. inst:GetFullName() .. " back into a Folder")
+        replaceClass(inst, "Folder")
+        return
+    end
+    print("RoBezy: Deleting " .. inst:GetFullName())
+    if isFolder and not inst:IsA("Folder") then
+        -- Folder on disk maps to a Model/Part in Studio: only remove the synced instances inside
+        for _, desc in ipairs(inst:GetDescendants()) do
+            if isSynced(desc) and desc.Parent then
+                forgetInstance(desc)
+                desc:Destroy()
+            end
+        end
+        return
+    end
+    forgetInstance(inst)
+    inst:Destroy()
+end
+
+local function ensureInstance(fsPath, leafClass, guid)
+    local known = byGuid(guid)
+    if known then return known end
     fsPath = string.gsub(fsPath, "\\", "/")
     local segments = string.split(fsPath, "/")
     if #segments == 0 then return nil end
@@ -708,25 +1116,30 @@ local function ensureInstance(fsPath, leafClass)
         local nameStr = segments[i]
         local isLast = (i == #segments)
         if isLast then
-             nameStr = string.gsub(nameStr, "%.server%.lua$", "")
-             nameStr = string.gsub(nameStr, "%.client%.lua$", "")
-             nameStr = string.gsub(nameStr, "%.lua$", "")
-             nameStr = string.gsub(nameStr, "%.json$", "")
-             if nameStr == "init" then return current end
+             nameStr = stripExtension(nameStr)
+             if nameStr == "init" then
+                 -- "Foo/init.server.lua" is the Script Foo itself; its folder may exist as a plain Folder
+                 if isScriptClass(leafClass) and current.ClassName ~= leafClass and current.Parent ~= game then
+                     current = replaceClass(current, leafClass)
+                 end
+                 return current
+             end
         end
-        local child = current:FindFirstChild(nameStr)
+        -- A script's folder may still be a plain Folder (it becomes the script at its init file)
+        local segClass = segmentClass(segments, i, leafClass)
+        local child = findSibling(current, nameStr, segClass) or (segClass and not isLast and findSibling(current, nameStr, "Folder"))
         if not child then
             if isLast then
                 local classToCreate = leafClass or "ModuleScript"
-                if classToCreate ~= "Script" and classToCreate ~= "LocalScript" and classToCreate ~= "ModuleScript" then
+                if not isContentClass(classToCreate) and classToCreate ~= "Folder" then
                     classToCreate = "ModuleScript"
                 end
                 child = Instance.new(classToCreate)
-                child.Name = nameStr
+                child.Name = (splitDuplicateSuffix(nameStr))
                 child.Parent = current
             else
                 child = Instance.new("Folder")
-                child.Name = nameStr
+                child.Name = (splitDuplicateSuffix(nameStr))
                 child.Parent = current
             end
         end
@@ -735,37 +1148,385 @@ local function ensureInstance(fsPath, leafClass)
     return current
 end
 
+local function applyWrite(change)
+    local inst = ensureInstance(change.path, change.class_name, change.guid)
+    if inst and isSynced(inst) and change.content then
+        print("RoBezy: Syncing " .. inst:GetFullName())
+        writeContent(inst, change.content)
+        DirtyScripts[inst] = nil 
+        rememberPaths(inst)
+    end
+    return inst
+end
+
+-- Rename / reparent the existing instance so it keeps its identity (references, selection, history)
+local function moveInstance(change)
+    local inst = change.old_path and findInstance(change.old_path, change.guid, change.class_name)
+    if not inst or inst.Parent == game then
+        -- Unknown source: fall back to creating it at the new location
+        if change.class_name == "Folder" then
+            local folder = ensureInstance(change.path, "Folder")
+            if folder then rememberPaths(folder) end
+            return folder
+        end
+        return applyWrite(change)
+    end
+
+    local path = string.gsub(change.path, "\\", "/")
+    local segments = string.split(path, "/")
+    local newName = (splitDuplicateSuffix(stripExtension(segments[#segments])))
+    table.remove(segments)
+    local newParent = ensureInstance(table.concat(segments, "/"), "Folder")
+    if not newParent then return nil end
+
+    if change.class_name and change.class_name ~= "Folder" and inst.ClassName ~= change.class_name then
+        inst = replaceClass(inst, change.class_name)
+    end
+
+    print("RoBezy: Moving " .. inst:GetFullName() .. " -> " .. newName)
+    inst.Name = newName
+    inst.Parent = newParent
+
+    if isSynced(inst) and change.content and readContent(inst) ~= change.content then
+        writeContent(inst, change.content)
+    end
+    DirtyScripts[inst] = nil
+    rememberPaths(inst)
+    return inst
+end
+
+-- === INSTANCE DESCRIPTIONS (.model.json / .meta.json, Rojo format) ===
+local function isModelPath(fsPath) return string.match(fsPath, "%.model%.json$") ~= nil end
+local function isMetaPath(fsPath) return string.match(fsPath, "%.meta%.json$") ~= nil end
+
+local function enumItem(current, value)
+    if typeof(current) ~= "EnumItem" then return value end
+    for _, item in ipairs(current.EnumType:GetEnumItems()) do
+        if item.Name == value or item.Value == value then return item end
+    end
+    return current
+end
+
+-- JSON -> Roblox value for a known type name ("Vector3", "Color3", ...)
+local function decodeTyped(typeName, v, current)
+    if typeName == "Vector3" then return Vector3.new(v[1], v[2], v[3])
+    elseif typeName == "Vector2" then return Vector2.new(v[1], v[2])
+    elseif typeName == "Color3" then return Color3.new(v[1], v[2], v[3])
+    elseif typeName == "Color3uint8" then return Color3.fromRGB(v[1], v[2], v[3])
+    elseif typeName == "UDim" then return UDim.new(v[1], v[2])
+    elseif typeName == "UDim2" then return UDim2.new(v[1][1], v[1][2], v[2][1], v[2][2])
+    elseif typeName == "BrickColor" then return BrickColor.new(v)
+    elseif typeName == "NumberRange" then return NumberRange.new(v[1], v[2])
+    elseif typeName == "CFrame" then
+        if v.position then
+            local p, o = v.position, v.orientation
+            if o then
+                return CFrame.new(p[1], p[2], p[3], o[1][1], o[1][2], o[1][3], o[2][1], o[2][2], o[2][3], o[3][1], o[3][2], o[3][3])
+            end
+            return CFrame.new(p[1], p[2], p[3])
+        end
+        return CFrame.new(v[1], v[2], v[3])
+    elseif typeName == "Enum" or typeName == "EnumItem" then return enumItem(current, v)
+    end
+    return v -- String, Bool, Float64, Int32, Content, ...
+end
+
+-- Explicit Rojo values ({"Vector3": [1, 2, 3]}) win; otherwise the property's current type decides
+local function decodeValue(current, value)
+    if type(value) == "table" and value[1] == nil then
+        local typeName, inner = next(value)
+        if typeName then return decodeTyped(typeName, inner, current) end
+    end
+    return decodeTyped(typeof(current), value, current)
+end
+
+-- Tags fire no per-instance event: the main loop compares synced instances' tags against these
+local SyncedTags = {} -- Instance -> tagKey when last seen or synced
+
+local function tagKey(inst)
+    local tags = CollectionService:GetTags(inst)
+    table.sort(tags)
+    return table.concat(tags, "\0")
+end
+
+-- Makes the instance's tags exactly `tags`
+local function setTags(inst, tags)
+    local wanted = {}
+    for _, tag in ipairs(tags) do
+        wanted[tag] = true
+        CollectionService:AddTag(inst, tag)
+    end
+    for _, tag in ipairs(CollectionService:GetTags(inst)) do
+        if not wanted[tag] then CollectionService:RemoveTag(inst, tag) end
+    end
+    if SyncedTags[inst] then SyncedTags[inst] = tagKey(inst) end
+end
+
+-- `exact`: the description is the whole attribute/tag set (script sidecars), so missing ones are removed
+-- and Enabled / RunContext fall back to their defaults
+local function applyDescription(inst, desc, exact)
+    local properties = desc.properties or {}
+    if exact and inst:IsA("BaseScript") then
+        -- Defaults are left out of sidecars
+        if properties.Enabled == nil then inst.Enabled = true end
+        if inst.ClassName == "Script" and properties.RunContext == nil then inst.RunContext = Enum.RunContext.Legacy end
+    end
+    for prop, value in pairs(properties) do
+        if prop ~= "Tags" then
+            local ok, err = pcall(function() inst[prop] = decodeValue(inst[prop], value) end)
+            if not ok then warn("RoBezy: Can't set " .. inst:GetFullName() .. "." .. prop .. ": " .. tostring(err)) end
+        end
+    end
+    if properties.Tags or exact then setTags(inst, properties.Tags or {}) end
+    local attributes = desc.attributes or {}
+    if exact then
+        for name, _ in pairs(inst:GetAttributes()) do
+            if attributes[name] == nil and name ~= ID_ATTRIBUTE then inst:SetAttribute(name, nil) end
+        end
+    end
+    for name, value in pairs(attributes) do
+        local ok, err = pcall(function() inst:SetAttribute(name, decodeValue(nil, value)) end)
+        if not ok then warn("RoBezy: Can't set attribute " .. name .. " on " .. inst:GetFullName() .. ": " .. tostring(err)) end
+    end
+end
+
+local function buildInstance(parent, name, desc, existing)
+    local className = desc.className or "Folder"
+    local inst = existing or parent:FindFirstChild(name)
+    if inst and inst.ClassName ~= className then inst = replaceClass(inst, className) end
+    if not inst then
+        local ok, created = pcall(function() return Instance.new(className) end)
+        if not ok then
+            warn("RoBezy: Unknown class " .. tostring(className))
+            return nil
+        end
+        inst = created
+        inst.Name = name
+    end
+    applyDescription(inst, desc)
+    inst.Parent = parent
+    for _, childDesc in ipairs(desc.children or {}) do
+        if childDesc.name then buildInstance(inst, childDesc.name, childDesc) end
+    end
+    return inst
+end
+
+-- Rojo also accepts PascalCase field names ("ClassName", "Children", ...)
+local DESCRIPTION_FIELDS = {
+    Name = "name", ClassName = "className", Properties = "properties", Attributes = "attributes",
+    Children = "children", IgnoreUnknownInstances = "ignoreUnknownInstances"
+}
+
+local function normalizeDescription(desc)
+    for pascal, camel in pairs(DESCRIPTION_FIELDS) do
+        if desc[camel] == nil and desc[pascal] ~= nil then desc[camel] = desc[pascal] end
+    end
+    for _, childDesc in ipairs(desc.children or {}) do
+        if type(childDesc) == "table" then normalizeDescription(childDesc) end
+    end
+    return desc
+end
+
+local function decodeDescription(change)
+    local ok, desc = pcall(function() return HttpService:JSONDecode(change.content or "") end)
+    if not ok or type(desc) ~= "table" then
+        warn("RoBezy: Invalid description in " .. tostring(change.path))
+        return nil
+    end
+    return normalizeDescription(desc)
+end
+
+-- "Remotes/Fire.model.json" -> RemoteEvent "Fire" inside Remotes
+local function applyModel(change)
+    local desc = decodeDescription(change)
+    if not desc then return nil end
+    local segments = string.split((string.gsub(change.path, "\\", "/")), "/")
+    local leaf = table.remove(segments)
+    local parent = ensureInstance(table.concat(segments, "/"), "Folder")
+    if not parent then return nil end
+    local nameStr = string.gsub(leaf, "%.model%.json$", "")
+    print("RoBezy: Building " .. parent:GetFullName() .. "." .. nameStr)
+    return buildInstance(parent, (splitDuplicateSuffix(nameStr)), desc, findSibling(parent, nameStr, nil))
+end
+
+-- "Dir/init.meta.json" describes Dir itself; "Dir/Foo.meta.json" describes the sibling Foo.
+-- A script's sidecar comes with the script's guid and class, so a Script and a ModuleScript both named Foo never mix up
+local function applyMeta(change)
+    local desc = decodeDescription(change)
+    if not desc then return nil end
+    local path = string.gsub(change.path, "\\", "/")
+    local target
+    if string.match(path, "init%.meta%.json$") then
+        target = byGuid(change.guid) or ensureInstance((string.gsub(path, "/?init%.meta%.json$", "")), "Folder")
+        if target and desc.className and target:IsA("Folder") and target.ClassName ~= desc.className and target.Parent ~= game then
+            target = replaceClass(target, desc.className)
+        end
+    else
+        target = findInstance((string.gsub(path, "%.meta%.json$", "")), change.guid, change.class_name)
+    end
+    if target then applyDescription(target, desc, isSynced(target)) end
+    return target
+end
+
 local function pollChanges()
     if not State.Connected then return end
-    local url = POLL_URL .. "?session_id=" .. State.SessionId
+    local url = POLL_URL .. "?session_id=" .. State.SessionId .. "&after=" .. State.PollCursor .. "&wait=25"
     local success, response = pcall(function() return HttpService:GetAsync(url, true) end)
     
     if success then
         local valid, changes = pcall(function() return HttpService:JSONDecode(response) end)
+        if not valid or type(changes) ~= "table" then return nil end
         if valid and changes and #changes > 0 then
              print("RoBezy: Received " .. #changes .. " changes")
              State.ApplyingChanges = true
+             local applied = {}
+             local retryFrom = nil -- Lowest seq that wasn't applied: the next poll starts below it
              for _, change in ipairs(changes) do
-                  if change.change_type == "write" then
-                       local inst = ensureInstance(change.path, change.class_name)
-                       if inst and inst:IsA("LuaSourceContainer") and change.content then
-                            print("RoBezy: Syncing " .. inst:GetFullName())
-                            inst.Source = change.content
-                            LastWrittenContent[inst] = change.content 
-                            DirtyScripts[inst] = nil 
+                  local ok, err = pcall(function()
+                       if change.change_type == "write" and isModelPath(change.path) then
+                            applyModel(change)
+                       elseif change.change_type == "write" and isMetaPath(change.path) then
+                            applyMeta(change)
+                       elseif change.change_type == "write" then
+                            local target = findInstance(change.path, change.guid, change.class_name)
+                            if target and DirtyScripts[target] then
+                                 -- Unsynced Studio edits: don't overwrite them, let the backend park a .conflict copy.
+                                 -- Not acked, so the disk content never becomes the synced base. If the post fails
+                                 -- the script stays dirty and its next push is still checked against the disk edit.
+                                 warn("RoBezy: Conflict on " .. target:GetFullName() .. " (edited in Studio and on disk)")
+                                 local studioContent = readContent(target)
+                                 local posted = postChanges({{
+                                     change_type = "conflict",
+                                     path = getInstancePath(target),
+                                     content = studioContent,
+                                     is_script = target:IsA("LuaSourceContainer"),
+                                     guid = getGuid(target),
+                                     class_name = target.ClassName
+                                 }})
+                                 if posted then
+                                     DirtyScripts[target] = nil
+                                     LastWrittenContent[target] = studioContent
+                                 end
+                                 return false
+                            else
+                                 applyWrite(change)
+                            end
+                       elseif change.change_type == "delete" then
+                            -- Removing a .meta.json keeps the instance as it is
+                            if not isMetaPath(change.path) then
+                                 deleteInstance(change.path, change.class_name == "Folder", change.guid, change.class_name)
+                            end
+                       elseif change.change_type == "rename" or change.change_type == "move" then
+                            if isMetaPath(change.path) then
+                                 applyMeta(change)
+                            elseif isModelPath(change.path) then
+                                 -- Keep the existing instance if we have it, then re-apply the description
+                                 if change.old_path and findInstance(change.old_path, nil) then moveInstance(change) end
+                                 applyModel(change)
+                            else
+                                 moveInstance(change)
+                            end
+                       end
+                       return true
+                  end)
+                  if ok and err then
+                       table.insert(applied, change.seq)
+                  else
+                       if not ok then
+                            warn("RoBezy: Failed to apply change to " .. tostring(change.path) .. ": " .. tostring(err))
                        end
+                       if change.seq and (not retryFrom or change.seq < retryFrom) then retryFrom = change.seq end
+                  end
+                  if change.seq and change.seq > State.PollCursor and not (retryFrom and change.seq >= retryFrom) then
+                       State.PollCursor = change.seq
                   end
              end
              State.ApplyingChanges = false
+             if #applied > 0 then
+                  pcall(function()
+                       local payload = { session_id = State.SessionId, seqs = applied }
+                       HttpService:PostAsync(ACK_URL, HttpService:JSONEncode(payload), Enum.HttpContentType.ApplicationJson, false)
+                  end)
+             end
              updateUI()
+             return #applied
         end
+        return #changes
     end
+    return nil
 end
 
 -- WATCHERS
+-- Studio-side rename / reparent / delete (Explorer deletes just set Parent = nil)
+local WatchedTables = {} -- LocalizationTables, checked for edits from the main loop
+
+local function checkTables()
+    if State.ApplyingChanges then return end
+    for tbl, _ in pairs(WatchedTables) do
+        if not tbl:IsDescendantOf(game) then
+            WatchedTables[tbl] = nil
+        elseif not DirtyScripts[tbl] and LastWrittenContent[tbl] ~= encodeCsv(tbl) then
+            print("RoBezy: Detected Change in " .. tbl.Name)
+            DirtyScripts[tbl] = true
+            updateUI()
+        end
+    end
+end
+
+local function checkTags()
+    if State.ApplyingChanges then return end
+    for inst, known in pairs(SyncedTags) do
+        if not inst:IsDescendantOf(game) then
+            SyncedTags[inst] = nil
+        else
+            local current = tagKey(inst)
+            if current ~= known then
+                SyncedTags[inst] = current
+                print("RoBezy: Detected tag change on " .. inst.Name)
+                DirtyScripts[inst] = true
+                updateUI()
+            end
+        end
+    end
+end
+
+-- Same-named siblings an instance leaves behind are renumbered ("Handler~2" -> "Handler")
+local function markRenumbered(oldPath)
+    local base = string.gsub(oldPath, "~%d+$", "")
+    for other, known in pairs(KnownPaths) do
+        if string.gsub(known, "~%d+$", "") == base and other:IsDescendantOf(game) and getInstancePath(other) ~= known then
+            MovedScripts[other] = true
+        end
+    end
+end
+
+local function markMoved(inst)
+    if not KnownPaths[inst] then return end
+    if inst:IsDescendantOf(game) and getInstancePath(inst) == KnownPaths[inst] then
+        MovedScripts[inst] = nil
+    else
+        MovedScripts[inst] = true
+        markRenumbered(KnownPaths[inst])
+    end
+    updateUI()
+end
+
 local function setupWatchers()
     local function watchScript(scriptInstance)
-        if not scriptInstance:IsA("LuaSourceContainer") then return end
+        if scriptInstance:IsA("Folder") or scriptInstance:IsA("Model") then
+            -- Renaming a container moves every script inside it
+            scriptInstance:GetPropertyChangedSignal("Name"):Connect(function()
+                for _, desc in ipairs(scriptInstance:GetDescendants()) do
+                    if isSynced(desc) then markMoved(desc) end
+                end
+            end)
+            return
+        end
+        if not isSynced(scriptInstance) then return end
+        getGuid(scriptInstance)
+        KnownPaths[scriptInstance] = KnownPaths[scriptInstance] or getInstancePath(scriptInstance)
+        scriptInstance:GetPropertyChangedSignal("Name"):Connect(function() markMoved(scriptInstance) end)
+        scriptInstance.AncestryChanged:Connect(function() markMoved(scriptInstance) end)
         scriptInstance.Changed:Connect(function(prop)
             if prop == "Source" then 
                 if State.ApplyingChanges then return end 
@@ -776,6 +1537,33 @@ local function setupWatchers()
                 updateUI()
             end
         end)
+        if scriptInstance:IsA("StringValue") then
+            scriptInstance:GetPropertyChangedSignal("Value"):Connect(function()
+                if State.ApplyingChanges then return end
+                if LastWrittenContent[scriptInstance] == scriptInstance.Value then return end
+                DirtyScripts[scriptInstance] = true
+                updateUI()
+            end)
+        elseif scriptInstance:IsA("LocalizationTable") then
+            -- Entry edits fire no event; the main loop compares tables against what was last synced
+            LastWrittenContent[scriptInstance] = LastWrittenContent[scriptInstance] or encodeCsv(scriptInstance)
+            WatchedTables[scriptInstance] = true
+        end
+        for _, prop in ipairs({"Enabled", "RunContext"}) do
+            pcall(function()
+                scriptInstance:GetPropertyChangedSignal(prop):Connect(function()
+                    if State.ApplyingChanges then return end
+                    DirtyScripts[scriptInstance] = true
+                    updateUI()
+                end)
+            end)
+        end
+        scriptInstance.AttributeChanged:Connect(function(name)
+            if State.ApplyingChanges or name == ID_ATTRIBUTE then return end
+            DirtyScripts[scriptInstance] = true
+            updateUI()
+        end)
+        SyncedTags[scriptInstance] = SyncedTags[scriptInstance] or tagKey(scriptInstance)
     end
     local services = {
         game:GetService("Workspace"), 
@@ -796,30 +1584,252 @@ end
 
 local function gatherProjectFiles()
     local files = {}
-    local function getPath(instance)
-        local path = instance.Name
-        local callback = instance.Parent
-        while callback and callback ~= game do
-            path = callback.Name .. "/" .. path
-            callback = callback.Parent
-        end
-        return path
-    end
     local services = {game.Workspace, game.ServerScriptService, game.ReplicatedStorage, game.ReplicatedFirst, game.StarterPlayer, game.StarterGui, game.StarterPack, game.ServerStorage, game.Lighting}
     for _, service in ipairs(services) do
         for _, desc in ipairs(service:GetDescendants()) do
-             if desc:IsA("LuaSourceContainer") and desc.Name ~= "RoBezy_Snapshot_Link" then
-                 local path = getPath(desc)
+             if isSynced(desc) then
+                 local path = getInstancePath(desc, "/")
                  local ext = ".lua"
                  if desc:IsA("LocalScript") then ext = ".client.lua"
-                 elseif desc:IsA("Script") then ext = ".server.lua" end
-                 table.insert(files, {path = path..ext, content = desc.Source})
+                 elseif desc:IsA("Script") then ext = ".server.lua"
+                 elseif desc:IsA("StringValue") then ext = ".txt"
+                 elseif desc:IsA("LocalizationTable") then ext = ".csv" end
+                 table.insert(files, withMeta({path = path..ext, content = readContent(desc), guid = getGuid(desc), class_name = desc.ClassName}, desc))
              end
         end
     end
     return files
 end
 
+-- === CHUNKED UPLOAD ===
+-- Places too big for one request go up in numbered chunks with CRC32 checksums before connecting
+local UPLOAD_CHUNK_BYTES = 512 * 1024
+local UPLOAD_ATTEMPTS = 3
+
+local CRC32_TABLE = {}
+for i = 0, 255 do
+    local c = i
+    for _ = 1, 8 do
+        if bit32.band(c, 1) == 1 then
+            c = bit32.bxor(0xEDB88320, bit32.rshift(c, 1))
+        else
+            c = bit32.rshift(c, 1)
+        end
+    end
+    CRC32_TABLE[i] = c
+end
+
+-- crc32(s) = crc32Update(0, s); crc32Update(crc32(a), b) = crc32(a .. b)
+local function crc32Update(crc, s)
+    crc = bit32.bnot(crc)
+    for i = 1, #s do
+        crc = bit32.bxor(CRC32_TABLE[bit32.band(bit32.bxor(crc, string.byte(s, i)), 0xFF)], bit32.rshift(crc, 8))
+        if i % 200000 == 0 then task.wait() end -- Keep Studio responsive on big places
+    end
+    return bit32.bnot(crc)
+end
+
+-- A copy of `file` carrying `content` (one piece of a split file)
+local function filePiece(file, content, more)
+    local piece = {}
+    for key, value in pairs(file) do piece[key] = value end
+    piece.content = content
+    piece.more = more
+    return piece
+end
+
+-- Pieces of a file whose encoded entry doesn't fit in one chunk, each encoding to at most UPLOAD_CHUNK_BYTES.
+-- Cuts never split a UTF-8 character; escaping can grow a piece, so oversized ones are cut again.
+local function splitFile(file)
+    local pieces, content, start = {}, file.content or "", 1
+    repeat
+        local length = math.floor(UPLOAD_CHUNK_BYTES / 2)
+        while true do
+            local stop = math.min(start + length - 1, #content)
+            while stop < #content and stop > start and bit32.band(string.byte(content, stop + 1), 0xC0) == 0x80 do
+                stop = stop - 1 -- Back to the end of a whole character
+            end
+            local piece = filePiece(file, string.sub(content, start, stop), stop < #content)
+            if #HttpService:JSONEncode(piece) <= UPLOAD_CHUNK_BYTES or length <= 1024 then
+                table.insert(pieces, piece)
+                start = stop + 1
+                break
+            end
+            length = math.floor(length / 2)
+        end
+    until start > #content
+    return pieces
+end
+
+-- Chunks are sized by their encoded JSON (escaping makes it larger than the raw source)
+local function splitIntoChunks(files)
+    local chunks, current, size = {}, {}, 2 -- "[]"
+    local function add(entry, entrySize)
+        if #current > 0 and size + entrySize > UPLOAD_CHUNK_BYTES then
+            table.insert(chunks, HttpService:JSONEncode(current))
+            current, size = {}, 2
+        end
+        table.insert(current, entry)
+        size = size + entrySize + 1 -- ","
+    end
+    for _, file in ipairs(files) do
+        local fileSize = #HttpService:JSONEncode(file)
+        if fileSize <= UPLOAD_CHUNK_BYTES then
+            add(file, fileSize)
+        else
+            for _, piece in ipairs(splitFile(file)) do add(piece, #HttpService:JSONEncode(piece)) end
+        end
+    end
+    if #current > 0 then table.insert(chunks, HttpService:JSONEncode(current)) end
+    return chunks
+end
+
+local function decodeStatus(ok, resp)
+    if not ok then return nil, tostring(resp) end
+    local decoded, status = pcall(function() return HttpService:JSONDecode(resp) end)
+    if not decoded then return nil, tostring(resp) end
+    if type(status) ~= "table" then return nil, tostring(status) end -- "error: ..."
+    return status
+end
+
+-- Returns the upload id once the backend has every chunk, or nil and the reason
+local function uploadFiles(sessionId, chunks, fileCount)
+    local sums, total = {}, 0
+    for i, data in ipairs(chunks) do
+        sums[i] = crc32Update(0, data)
+        total = crc32Update(total, data)
+    end
+
+    local status, err = decodeStatus(pcall(function()
+        local manifest = { session_id = sessionId, file_count = fileCount, chunk_count = #chunks, total_crc32 = total }
+        return HttpService:PostAsync(UPLOAD_URL, HttpService:JSONEncode(manifest), Enum.HttpContentType.ApplicationJson, false)
+    end))
+    if not status then return nil, err end
+    local uploadId = status.upload_id
+    local chunkUrl = UPLOAD_URL .. "/" .. uploadId .. "/chunk"
+
+    for _ = 1, UPLOAD_ATTEMPTS do
+        for _, seq in ipairs(status.missing) do
+            local reply, chunkErr = decodeStatus(pcall(function()
+                local payload = { seq = seq, crc32 = sums[seq], data = chunks[seq] }
+                return HttpService:PostAsync(chunkUrl, HttpService:JSONEncode(payload), Enum.HttpContentType.ApplicationJson, false)
+            end))
+            if reply then
+                status = reply
+            else
+                warn("RoBezy: Upload chunk " .. seq .. "/" .. #chunks .. " failed: " .. chunkErr)
+            end
+        end
+        if status.complete then return uploadId end
+        -- Resume: ask which chunks are still missing and resend only those
+        local current, statusErr = decodeStatus(pcall(function()
+            return HttpService:GetAsync(UPLOAD_URL .. "/" .. uploadId, true)
+        end))
+        if not current then return nil, statusErr end
+        status = current
+        if status.complete then return uploadId end
+    end
+    return nil, "chunks still missing: " .. table.concat(status.missing, ", ")
+end
+
+local function setButtonText(text)
+    if connectBtn then connectBtn.Text = text end
+end
+
+-- CONNECT: uploads the project and opens (or resumes) the session stored in RoBezyConfig
+local function connect()
+    setButtonText("CONNECTING...")
+    
+    -- RESOLVE SESSION ID
+    local ServerStorage = game:GetService("ServerStorage")
+    local configValue = ServerStorage:FindFirstChild("RoBezyConfig")
+    local storedId = nil
+    if configValue and configValue:IsA("StringValue") then
+         local s, c = pcall(function() return HttpService:JSONDecode(configValue.Value) end)
+         if s and c.id then storedId = c.id end
+    end
+    local sessionId = storedId or HttpService:GenerateGUID(false)
+    State.SessionId = sessionId
+    local projectId = storedId
+    
+    -- GATHER FILES
+    local allFiles = gatherProjectFiles()
+    local filesToSend = allFiles
+    local uploadId = nil
+    local uploadErr = nil
+
+    -- More than one request's worth: upload in chunks first, then connect with the upload id
+    local chunks = splitIntoChunks(allFiles)
+    if #chunks > 1 then
+        setButtonText("UPLOADING...")
+        uploadId, uploadErr = uploadFiles(sessionId, chunks, #allFiles)
+        filesToSend = {}
+    end
+    
+    local payload = {
+        place_id = game.PlaceId,
+        place_name = game.Name,
+        session_id = sessionId,
+        project_id = projectId,
+        files = filesToSend,
+        upload_id = uploadId
+    }
+
+    local success, resp = false, uploadErr and ("upload failed: " .. uploadErr)
+    if not uploadErr then
+        success, resp = pcall(function()
+             return HttpService:PostAsync(CONNECT_URL, HttpService:JSONEncode(payload), Enum.HttpContentType.ApplicationJson, false)
+        end)
+    end
+    local data = success and decodeStatus(true, resp)
+    if success and not data then
+        success = false -- "error: ..." from the backend
+    end
+    
+    if success then
+        State.SessionId = data.session_id
+        State.ProjectId = data.project_id
+        State.Connected = true
+        State.PollCursor = 0 -- From the start of whatever the app still holds for this session
+
+        -- What the app did with files that changed on disk while we were apart
+        local plan = data.reconcile
+        if type(plan) == "table" and type(plan.summary) == "table" then
+            local s = plan.summary
+            print("RoBezy: Connected (" .. tostring(plan.policy) .. "): " .. (s.disk_newer or 0) .. " disk newer, " .. (s.studio_newer or 0) .. " Studio newer, " .. (s.disk_only or 0) .. " only on disk")
+            if (s.conflicts or 0) > 0 then
+                warn("RoBezy: " .. s.conflicts .. " script(s) changed in Studio and on disk. Disk was kept; Studio's versions are saved as .conflict files")
+            end
+        end
+        
+        -- The connect upload is the new baseline for rename/delete detection
+        for inst, _ in pairs(KnownPaths) do
+            if inst:IsDescendantOf(game) then
+                KnownPaths[inst] = getInstancePath(inst)
+            else
+                KnownPaths[inst] = nil
+            end
+        end
+        MovedScripts = {}
+        
+         pcall(function()
+            local sv = game:GetService("ServerStorage"):FindFirstChild("RoBezyConfig")
+            if not sv then 
+                sv = Instance.new("StringValue")
+                sv.Name = "RoBezyConfig"
+                sv.Parent = game:GetService("ServerStorage")
+            end
+            sv.Value = HttpService:JSONEncode({id = State.SessionId})
+        end)
+        updateUI()
+    else
+        warn("RoBezy Connect Failed: " .. tostring(resp))
+        setButtonText("FAILED")
+        task.wait(1)
+        updateUI()
+    end
+end
+
 -- BUTTON HANDLER
 if connectBtn then
     connectBtn.MouseButton1Click:Connect(function()
@@ -842,59 +1852,7 @@ if connectBtn then
             State.SessionId = ""
              updateUI()
         else
-            -- CONNECT
-            connectBtn.Text = "CONNECTING..."
-            
-            -- RESOLVE SESSION ID
-            local ServerStorage = game:GetService("ServerStorage")
-            local configValue = ServerStorage:FindFirstChild("RoBezyConfig")
-            local storedId = nil
-            if configValue and configValue:IsA("StringValue") then
-                 local s, c = pcall(function() return HttpService:JSONDecode(configValue.Value) end)
-                 if s and c.id then storedId = c.id end
-            end
-            local sessionId = storedId or HttpService:GenerateGUID(false)
-            State.SessionId = sessionId
-            local projectId = storedId
-            
-            -- GATHER FILES
-            local allFiles = gatherProjectFiles()
-            local filesToSend = allFiles
-            
-            local payload = {
-                place_id = game.PlaceId,
-                place_name = game.Name,
-                session_id = sessionId,
-                project_id = projectId,
-                files = filesToSend
-            }
-        
-            local success, resp = pcall(function()
-                 return HttpService:PostAsync(CONNECT_URL, HttpService:JSONEncode(payload), Enum.HttpContentType.ApplicationJson, false)
-            end)
-            
-            if success then
-                local data = HttpService:JSONDecode(resp)
-                State.SessionId = data.session_id
-                State.ProjectId = data.project_id
-                State.Connected = true
-                
-                 pcall(function()
-                    local sv = game:GetService("ServerStorage"):FindFirstChild("RoBezyConfig")
-                    if not sv then 
-                        sv = Instance.new("StringValue")
-                        sv.Name = "RoBezyConfig"
-                        sv.Parent = game:GetService("ServerStorage")
-                    end
-                    sv.Value = HttpService:JSONEncode({id = State.SessionId})
-                end)
-                updateUI()
-            else
-                warn("RoBezy Connect Failed: " .. tostring(resp))
-                connectBtn.Text = "FAILED"
-                task.wait(1)
-                updateUI()
-            end
+            connect()
         end
     end)
 end
@@ -905,10 +1863,16 @@ end)
 
 local function sendHeartbeat()
     if not State.Connected then return end
-    pcall(function()
+    local ok, resp = pcall(function()
         local payload = { session_id = State.SessionId }
-        HttpService:PostAsync(HEARTBEAT_URL, HttpService:JSONEncode(payload), Enum.HttpContentType.ApplicationJson, false)
+        return HttpService:PostAsync(HEARTBEAT_URL, HttpService:JSONEncode(payload), Enum.HttpContentType.ApplicationJson, false)
     end)
+    -- The app restarted without our session (or dropped it): connect again with the same ids
+    if ok and string.find(resp, "unknown_session", 1, true) then
+        warn("RoBezy: The app lost this session, reconnecting...")
+        State.Connected = false
+        connect()
+    end
 end
 
 -- MAIN LOOP
@@ -917,13 +1881,32 @@ task.spawn(function()
     local tick = 0
     while true do
         checkAppStatus()
-        pollChanges() 
         tick = tick + 1
         if tick % 10 == 0 then sendHeartbeat() end
+        if State.Connected and tick % 5 == 0 then
+            checkTables()
+            checkTags()
+        end
         task.wait(1)
     end
 end)
 
+-- CHANGE LOOP: long-polls the backend, so disk edits arrive as soon as they are queued
+task.spawn(function()
+    while true do
+        if State.Connected then
+            local started = tick()
+            local received = pollChanges()
+            -- Failed, nothing applied, or an empty answer that came back early (older backend): don't spin
+            if received == nil or (received == 0 and tick() - started < 1) then
+                task.wait(1)
+            end
+        else
+            task.wait(1)
+        end
+    end
+end)
+
 print("RoBezy Professional UI Loaded (v1.1.11 Safe+Toolbar)")
 ]]></ProtectedString>
 		</Properties>
@@ -0,0 +1,128 @@
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+
+/// Per-project settings, kept in the bound folder so they travel with the project.
+/// Plain `.json`, so the watcher never syncs it to Studio.
+pub const CONFIG_FILE: &str = "robezy.json";
+
+/// Extension used for new script files. Existing files keep theirs until migrated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptExtension {
+    #[default]
+    Lua,
+    Luau,
+}
+
+impl ScriptExtension {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim_start_matches('.') {
+            "lua" => Ok(ScriptExtension::Lua),
+            "luau" => Ok(ScriptExtension::Luau),
+            other => Err(format!("unknown script extension '{}'", other)),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScriptExtension::Lua => "lua",
+            ScriptExtension::Luau => "luau",
+        }
+    }
+
+    /// The flavour a script file already uses ("Foo.server.luau" -> Luau), None for other files.
+    pub fn of_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "lua" => Some(ScriptExtension::Lua),
+            "luau" => Some(ScriptExtension::Luau),
+            _ => None,
+        }
+    }
+}
+
+/// Which side wins when a plugin connects and Studio and disk disagree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReconnectPolicy {
+    /// The side that changed since the last sync wins; edits on both sides become conflicts.
+    #[default]
+    Auto,
+    /// Studio overwrites disk (files only on disk are left alone).
+    Studio,
+    /// Disk is pushed to Studio (files only in Studio are still written to disk).
+    Disk,
+}
+
+impl ReconnectPolicy {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "auto" => Ok(ReconnectPolicy::Auto),
+            "studio" => Ok(ReconnectPolicy::Studio),
+            "disk" => Ok(ReconnectPolicy::Disk),
+            other => Err(format!("unknown reconnect policy '{}'", other)),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReconnectPolicy::Auto => "auto",
+            ReconnectPolicy::Studio => "studio",
+            ReconnectPolicy::Disk => "disk",
+        }
+    }
+}
+
+/// How line endings of synced text files are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEndings {
+    /// CRLF becomes LF in both directions, so files hash the same on every OS.
+    #[default]
+    Lf,
+    /// Line endings (and an existing UTF-8 BOM on disk) are left as they are.
+    Preserve,
+}
+
+/// What happens to a synced file on disk that isn't valid UTF-8 (or UTF-16 with a BOM).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NonUtf8 {
+    /// Not synced; the backend logs it.
+    #[default]
+    Skip,
+    /// Read as Latin-1 (every byte is one character). Written back as UTF-8.
+    Latin1,
+    /// Invalid bytes become U+FFFD.
+    Lossy,
+}
+
+/// Contents of `robezy.json`. Missing fields fall back to their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProjectConfig {
+    pub script_extension: ScriptExtension,
+    pub reconnect_policy: ReconnectPolicy,
+    pub line_endings: LineEndings,
+    pub non_utf8: NonUtf8,
+}
+
+impl ProjectConfig {
+    /// Reads `robezy.json` from a bound folder. A missing or broken file gives the defaults.
+    pub fn load(root_dir: &Path) -> Self {
+        let path = root_dir.join(CONFIG_FILE);
+        let raw = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(_) => return Self::default(),
+        };
+        serde_json::from_str(&raw).unwrap_or_else(|e| {
+            eprintln!("RoBezy: Ignoring invalid {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, root_dir: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        crate::robezy::fs::write_atomic(&root_dir.join(CONFIG_FILE), json + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", CONFIG_FILE, e))
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::robezy::fs::NativeFileManager;
+use crate::robezy::model::{file_kind, FileKind};
+use crate::robezy::session::{FileChange, SharedQueue};
+
+/// A file that was edited in Studio and on disk since the last sync.
+/// The disk version stays in place, Studio's version is written next to it as `<path>.conflict`,
+/// and outbound changes for the path are held back from `poll_changes` until it is resolved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conflict {
+    pub path: String,          // Relative Path ("ServerScriptService/Foo.server.lua")
+    pub conflict_path: String, // Studio's version ("ServerScriptService/Foo.server.lua.conflict")
+    pub guid: Option<String>,
+    pub class_name: Option<String>,
+    pub detected_at: u64, // Unix seconds
+}
+
+// Map Relative Path -> Open Conflict
+pub type ConflictMap = Arc<Mutex<HashMap<String, Conflict>>>;
+
+/// "Foo.server.lua" -> "Foo.server.lua.conflict" (not a synced extension, so the watcher skips it)
+pub fn conflict_path(relative_path: &str) -> String {
+    format!("{}.conflict", relative_path)
+}
+
+fn normalize(p: &Path) -> String {
+    p.to_string_lossy().replace("\\", "/")
+}
+
+/// Which side wins when resolving.
+pub enum Resolution {
+    Studio,
+    Disk,
+    Merged(String),
+}
+
+impl Resolution {
+    pub fn parse(choice: &str, content: Option<String>) -> Result<Self, String> {
+        match choice {
+            "studio" => Ok(Resolution::Studio),
+            "disk" => Ok(Resolution::Disk),
+            "merged" => content.map(Resolution::Merged).ok_or_else(|| "merged resolution needs content".to_string()),
+            other => Err(format!("unknown resolution '{}'", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::Studio => "studio",
+            Resolution::Disk => "disk",
+            Resolution::Merged(_) => "merged",
+        }
+    }
+}
+
+/// Records Studio's version of a conflicting file. Returns the conflict if it is new
+/// (so the caller can broadcast it); a conflict that is already open just gets its copy refreshed.
+pub async fn raise(fm: &NativeFileManager, conflicts: &ConflictMap, relative_path: &Path, guid: Option<String>, class_name: Option<String>, studio_content: &str) -> Result<Option<Conflict>, String> {
+    let path = normalize(relative_path);
+    let copy = conflict_path(&path);
+    fm.write_relative(Path::new(&copy), studio_content).await?;
+    Ok(open(conflicts, &path, guid, class_name))
+}
+
+/// Opens a conflict for `path` once Studio's copy is on disk. None if it was already open.
+pub fn open(conflicts: &ConflictMap, path: &str, guid: Option<String>, class_name: Option<String>) -> Option<Conflict> {
+    let mut map = conflicts.lock().unwrap();
+    if map.contains_key(path) {
+        return None;
+    }
+    let conflict = Conflict {
+        path: path.to_string(),
+        conflict_path: conflict_path(path),
+        guid,
+        class_name,
+        detected_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    map.insert(path.to_string(), conflict.clone());
+    Some(conflict)
+}
+
+/// Applies the chosen side, removes the `.conflict` copy and releases the path.
+pub async fn resolve(fm: &NativeFileManager, queue: &SharedQueue, conflicts: &ConflictMap, path: &str, resolution: &Resolution) -> Result<(), String> {
+    let conflict = conflicts.lock().unwrap().get(path).cloned()
+        .ok_or_else(|| format!("no conflict for {}", path))?;
+    let relative = Path::new(&conflict.path);
+
+    // Content Studio should end up with (None: Studio already has it)
+    let outbound = match resolution {
+        Resolution::Studio => {
+            let studio = fm.read_relative(Path::new(&conflict.conflict_path)).await?;
+            fm.write_relative(relative, &studio).await?;
+            fm.mark_synced(relative, &studio);
+            None
+        },
+        Resolution::Disk => Some(fm.read_relative(relative).await?),
+        Resolution::Merged(content) => {
+            let content = fm.normalize_text(content);
+            fm.write_relative(relative, &content).await?;
+            Some(content)
+        },
+    };
+
+    {
+        let mut q = queue.lock().unwrap();
+        q.retain(|c| c.path != conflict.path);
+        if let Some(content) = outbound {
+            q.push(FileChange {
+                change_type: "write".to_string(),
+                path: conflict.path.clone(),
+                content: Some(content),
+                is_script: matches!(file_kind(&conflict.path), Some(FileKind::Script(_))),
+                guid: conflict.guid.clone(),
+                class_name: conflict.class_name.clone(),
+                ..Default::default()
+            });
+        }
+    }
+
+    fm.remove_relative(Path::new(&conflict.conflict_path)).await?;
+    fm.persist_index().await;
+    conflicts.lock().unwrap().remove(path);
+    Ok(())
+}
@@ -0,0 +1,224 @@
+// Line-based unified diffs (`diff -u` style) for previews, using Myers' O(ND) algorithm.
+
+const CONTEXT: usize = 3;
+// Past this many cells of search history the diff falls back to "replace everything"
+const MAX_TRACE_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy)]
+enum Edit {
+    Equal(usize), // Old index
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Lines including their "\n", so a missing final newline shows up as a change.
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn edit_script(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max as isize {
+        if (trace.len() + 1) * v.len() > MAX_TRACE_CELLS {
+            let mut edits: Vec<Edit> = (0..a.len()).map(Edit::Delete).collect();
+            edits.extend((0..b.len()).map(Edit::Insert));
+            return edits;
+        }
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) { v[idx + 1] } else { v[idx - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    // Walk the search history back from the end to recover the path
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let idx = (k + offset) as usize;
+        let prev_k = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) { k + 1 } else { k - 1 };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal((x - 1) as usize));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert((y - 1) as usize));
+            } else {
+                edits.push(Edit::Delete((x - 1) as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+fn push_line(out: &mut String, tag: char, line: &str) {
+    out.push(tag);
+    match line.strip_suffix('\n') {
+        Some(text) => {
+            out.push_str(text);
+            out.push('\n');
+        },
+        None => {
+            out.push_str(line);
+            out.push_str("\n\\ No newline at end of file\n");
+        },
+    }
+}
+
+/// Unified diff from `old` to `new` with 3 lines of context. Empty when the texts are equal.
+/// `old_name` / `new_name` go in the `---` / `+++` headers (e.g. "a/Foo.lua", "/dev/null").
+pub fn unified(old_name: &str, new_name: &str, old: &str, new: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+    let (a, b) = (split_lines(old), split_lines(new));
+    let edits = edit_script(&a, &b);
+
+    // Position in both files before each edit (for hunk headers)
+    let mut positions = Vec::with_capacity(edits.len());
+    let (mut i, mut j) = (0, 0);
+    for edit in &edits {
+        positions.push((i, j));
+        match edit {
+            Edit::Equal(_) => { i += 1; j += 1; },
+            Edit::Delete(_) => i += 1,
+            Edit::Insert(_) => j += 1,
+        }
+    }
+
+    let changes: Vec<usize> = edits.iter().enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let mut c = 0;
+    while c < changes.len() {
+        // Changes with at most two contexts of equal lines between them share a hunk
+        let first = changes[c];
+        let mut last = first;
+        while c + 1 < changes.len() && changes[c + 1] - last - 1 <= 2 * CONTEXT {
+            c += 1;
+            last = changes[c];
+        }
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(edits.len());
+
+        let (old_len, new_len) = edits[start..end].iter().fold((0, 0), |(o, n), e| match e {
+            Edit::Equal(_) => (o + 1, n + 1),
+            Edit::Delete(_) => (o + 1, n),
+            Edit::Insert(_) => (o, n + 1),
+        });
+        let (old_pos, new_pos) = positions[start];
+        let old_start = if old_len == 0 { old_pos } else { old_pos + 1 };
+        let new_start = if new_len == 0 { new_pos } else { new_pos + 1 };
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_len, new_start, new_len));
+        for edit in &edits[start..end] {
+            match *edit {
+                Edit::Equal(i) => push_line(&mut out, ' ', a[i]),
+                Edit::Delete(i) => push_line(&mut out, '-', a[i]),
+                Edit::Insert(j) => push_line(&mut out, '+', b[j]),
+            }
+        }
+        c += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "1\n2\n...20\n" with the lines in `changed` replaced
+    fn numbered(changed: &[usize]) -> String {
+        (1..=20).map(|i| if changed.contains(&i) { format!("{} changed\n", i) } else { format!("{}\n", i) }).collect()
+    }
+
+    #[test]
+    fn equal_texts_have_no_diff() {
+        assert_eq!(unified("a/Foo.lua", "b/Foo.lua", "x\ny\n", "x\ny\n"), "");
+        assert_eq!(unified("a/Foo.lua", "b/Foo.lua", "", ""), "");
+    }
+
+    #[test]
+    fn new_file_is_a_pure_insert() {
+        assert_eq!(
+            unified("/dev/null", "b/Foo.lua", "", "a\nb\n"),
+            "--- /dev/null\n+++ b/Foo.lua\n@@ -0,0 +1,2 @@\n+a\n+b\n",
+        );
+    }
+
+    #[test]
+    fn deleted_file_is_a_pure_delete() {
+        assert_eq!(
+            unified("a/Foo.lua", "/dev/null", "a\nb\n", ""),
+            "--- a/Foo.lua\n+++ /dev/null\n@@ -1,2 +0,0 @@\n-a\n-b\n",
+        );
+    }
+
+    #[test]
+    fn missing_final_newline_is_a_change() {
+        assert_eq!(
+            unified("a/Foo.lua", "b/Foo.lua", "a\nb\n", "a\nb"),
+            "--- a/Foo.lua\n+++ b/Foo.lua\n@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n",
+        );
+    }
+
+    #[test]
+    fn changes_two_contexts_apart_share_a_hunk() {
+        // Lines 5 and 12 change: 6 equal lines (2 x CONTEXT) between them
+        let diff = unified("a/x", "b/x", &numbered(&[]), &numbered(&[5, 12]));
+        assert_eq!(diff.matches("@@ -").count(), 1);
+        assert!(diff.contains("@@ -2,14 +2,14 @@\n"));
+    }
+
+    #[test]
+    fn changes_further_apart_get_their_own_hunks() {
+        // Lines 5 and 13 change: 7 equal lines between them
+        let diff = unified("a/x", "b/x", &numbered(&[]), &numbered(&[5, 13]));
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -2,7 +2,7 @@\n"));
+        assert!(diff.contains("@@ -10,7 +10,7 @@\n"));
+    }
+
+    #[test]
+    fn huge_rewrites_fall_back_to_replacing_everything() {
+        // Too far apart for the search history budget: every old line goes, every new line comes,
+        // even the one they share
+        let old: String = (0..3000).map(|i| format!("old {}\n", i)).chain(["shared\n".to_string()]).collect();
+        let new: String = (0..3000).map(|i| format!("new {}\n", i)).chain(["shared\n".to_string()]).collect();
+        let (a, b) = (split_lines(&old), split_lines(&new));
+        assert!((a.len() + b.len()) * (2 * (a.len() + b.len()) + 3) > MAX_TRACE_CELLS);
+
+        let diff = unified("a/x", "b/x", &old, &new);
+        assert!(diff.contains("@@ -1,3001 +1,3001 @@\n"));
+        assert!(diff.contains("-shared\n"));
+        assert!(diff.contains("+shared\n"));
+        assert!(!diff.lines().skip(3).any(|line| line.starts_with(' ')));
+    }
+}
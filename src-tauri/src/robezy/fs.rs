@@ -1,106 +1,1109 @@
 use std::path::{Path, PathBuf};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
 use tokio::fs;
+use crate::robezy::session::IgnoreMap;
+use crate::robezy::model::{self, FileKind, InstanceDescription, ScriptMeta};
+use crate::robezy::config::{LineEndings, ProjectConfig, ScriptExtension};
+use crate::robezy::text;
+use crate::robezy::project::{ProjectMap, PROJECT_FILE};
+use crate::robezy::sourcemap::SourceMap;
+use crate::robezy::ignore::{IgnoreRules, IGNORE_FILE};
+use crate::safe_path;
+
+// How long the watcher ignores a path after the backend itself touched it
+const SELF_WRITE_IGNORE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Backend-owned folder inside every bound folder (never synced to Studio).
+pub const INTERNAL_DIR: &str = ".robezy";
+const INDEX_FILE: &str = "index.json";
+const INDEX_VERSION: u32 = 1;
+
+/// Suffix of the temp files `write_atomic` renames into place (".Foo.lua.1a2b3c4d.robezy-tmp").
+pub const TEMP_SUFFIX: &str = ".robezy-tmp";
+
+/// True for `write_atomic`'s temp files. The watcher only reacts to the rename that replaces the real file.
+pub fn is_temp_file(path: &Path) -> bool {
+    path.file_name().map(|n| n.to_string_lossy().ends_with(TEMP_SUFFIX)).unwrap_or(false)
+}
+
+/// Writes to a temp file in the same folder, fsyncs it and renames it over `path`, so the
+/// watcher (or a crash) never sees a half-written file: only the old content or the new.
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> std::io::Result<()> {
+    use std::io::Write;
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = parent.join(format!(".{}.{}{}", name, &uuid::Uuid::new_v4().to_string()[..8], TEMP_SUFFIX));
+
+    let written = std::fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(content.as_ref())?;
+        file.sync_all()
+    }).and_then(|_| std::fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+        return written;
+    }
+    // Make the rename itself durable (folders can't be opened for this on Windows)
+    #[cfg(unix)]
+    if let Ok(dir) = std::fs::File::open(if parent.as_os_str().is_empty() { Path::new(".") } else { parent }) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// The plugin numbers siblings with the same Name and ClassName by their order in the parent
+/// ("Handler", "Handler~2", ...) in every instance path segment, so paths are stable and
+/// reversible. This suffix is only the fallback for different names that sanitize to the same file,
+/// and for files that would share a sidecar (Script "Foo" next to ModuleScript "Foo").
+fn with_duplicate_suffix(base: &Path, n: usize) -> PathBuf {
+    if n <= 1 {
+        return base.to_path_buf();
+    }
+    let file_name = base.file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (stem, ext) = match file_name.find('.') {
+        Some(idx) => file_name.split_at(idx),
+        None => (file_name.as_str(), ""),
+    };
+    base.with_file_name(format!("{}~{}{}", stem, n, ext))
+}
 
 #[derive(Clone)]
 pub struct NativeFileManager {
     pub root_dir: PathBuf,
     state: Arc<Mutex<FileManagerState>>,
+    ignore_paths: IgnoreMap, // Shared with the session watcher (Anti-Loop)
+    sourcemap: Arc<Mutex<SourceMap>>, // sourcemap.json for luau-lsp
+}
+
+/// Keeps an instance path segment's sibling number ("Handler~2") and sanitizes the name before it.
+fn sanitize_segment(segment: &str) -> String {
+    let (name, number) = match segment.rsplit_once('~') {
+        Some((name, n)) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => (name, Some(n)),
+        _ => (segment, None),
+    };
+    let safe: String = name.chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .collect();
+    match number {
+        Some(n) if n != "1" => format!("{}~{}", safe, n),
+        _ => safe,
+    }
+}
+
+/// "ServerScriptService.Handler~2" -> "ServerScriptService.Handler": the instance path without its sibling number.
+fn without_sibling_number(instance_path: &str) -> &str {
+    match instance_path.rsplit_once('~') {
+        Some((rest, n)) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => rest,
+        _ => instance_path,
+    }
+}
+
+/// SHA-256 of file content (hex). Used as the "last synced" base for conflict detection.
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// One instance we own a file for. Persisted in `.robezy/index.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub path: PathBuf, // Relative Path
+    #[serde(default)]
+    pub instance_path: String, // "ServerScriptService.Foo"
+    #[serde(default)]
+    pub class_name: Option<String>,
+    #[serde(default)]
+    pub hash: Option<String>, // Content both Studio and disk last agreed on
+}
+
+/// A script's ClassName changed for a GUID we already owned a file for.
+#[derive(Debug, Clone)]
+pub struct ClassChange {
+    pub old_path: PathBuf, // Relative Path before the rename
+    pub old_class: Option<String>,
+    pub new_class: Option<String>,
+}
+
+/// What `write_file_guid` did on disk.
+#[derive(Debug, Clone)]
+pub struct WriteOutcome {
+    pub path: PathBuf, // Relative Path written
+    pub class_change: Option<ClassChange>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedIndex {
+    version: u32,
+    entries: HashMap<String, IndexEntry>, // GUID -> Entry
+    #[serde(default)]
+    unowned: HashMap<PathBuf, String>, // Relative Path -> Hash
 }
 
 struct FileManagerState {
-    guid_to_path: HashMap<String, PathBuf>, // GUID -> Relative Path
+    entries: HashMap<String, IndexEntry>,   // GUID -> Entry (Relative Path, class...)
     path_to_guid: HashMap<PathBuf, String>, // Relative Path -> GUID
+    // Relative Path -> Last synced hash, for files Studio accepted before any GUID owned them
+    // (files created on disk). The entry that claims the path takes the hash over.
+    unowned: HashMap<PathBuf, String>,
+    // GUIDs Studio reported since the app started. Entries loaded from disk that are not live
+    // may belong to instances deleted while the app was closed, so their paths can be taken.
+    live: HashSet<String>,
+    config: ProjectConfig, // robezy.json
+    project: Option<ProjectMap>, // default.project.json, if the folder is a Rojo project
+    synced_text: HashMap<String, String>, // GUID -> Last synced content (memory only, for previews)
+    ignore: IgnoreRules, // Built-in defaults + .robezyignore
+}
+
+impl FileManagerState {
+    fn path_of(&self, guid: &str) -> Option<&PathBuf> {
+        self.entries.get(guid).map(|e| &e.path)
+    }
+
+    /// Inserts/replaces the entry for a GUID, keeping both maps consistent.
+    /// Whoever owned `entry.path` before loses it.
+    fn insert(&mut self, guid: &str, mut entry: IndexEntry) {
+        if let Some(hash) = self.unowned.remove(&entry.path) {
+            entry.hash.get_or_insert(hash);
+        }
+        if let Some(previous) = self.entries.remove(guid) {
+            self.path_to_guid.remove(&previous.path);
+        }
+        if let Some(owner) = self.path_to_guid.insert(entry.path.clone(), guid.to_string()) {
+            if owner != guid {
+                self.entries.remove(&owner);
+            }
+        }
+        self.entries.insert(guid.to_string(), entry);
+        self.live.insert(guid.to_string());
+    }
+
+    fn remove_path(&mut self, path: &Path) -> Option<String> {
+        let guid = self.path_to_guid.remove(path)?;
+        self.entries.remove(&guid);
+        Some(guid)
+    }
 }
 
 impl NativeFileManager {
-    pub fn new(root_dir: impl Into<PathBuf>) -> Self {
+    pub fn new(root_dir: impl Into<PathBuf>, ignore_paths: IgnoreMap) -> Self {
+        let root_dir = root_dir.into();
+        let (entries, unowned) = Self::load_index(&root_dir);
+        let path_to_guid = entries.iter()
+            .map(|(guid, entry)| (entry.path.clone(), guid.clone()))
+            .collect();
         Self {
-            root_dir: root_dir.into(),
+            root_dir: root_dir.clone(),
             state: Arc::new(Mutex::new(FileManagerState {
-                guid_to_path: HashMap::new(),
-                path_to_guid: HashMap::new(),
+                entries,
+                path_to_guid,
+                unowned,
+                live: HashSet::new(),
+                config: ProjectConfig::load(&root_dir),
+                project: Self::load_project(&root_dir),
+                synced_text: HashMap::new(),
+                ignore: IgnoreRules::load(&root_dir),
             })),
+            ignore_paths,
+            sourcemap: Arc::new(Mutex::new(SourceMap::new())),
         }
     }
 
-    /// Resolves the intended path for a GUID.
-    /// If collision occurs, assigns a suffixed path (e.g. Script_1.lua).
-    /// Returns the RELATIVE path.
-    pub fn assign_path(&self, guid: &str, instance_path: &str, is_script: bool, class_name: Option<&str>) -> Option<PathBuf> {
-        let mut state = self.state.lock().unwrap();
+    pub fn config(&self) -> ProjectConfig {
+        self.state.lock().unwrap().config.clone()
+    }
+
+    /// Re-reads `robezy.json` after it was edited on disk.
+    pub fn reload_config(&self) {
+        self.state.lock().unwrap().config = ProjectConfig::load(&self.root_dir);
+    }
+
+    /// Reads a synced file (absolute or relative path) as the text Studio gets: BOM dropped,
+    /// line endings and non-UTF-8 bytes handled as `robezy.json` says.
+    pub fn read_text(&self, path: &Path) -> Result<String, String> {
+        let full = self.root_dir.join(path);
+        let shown = full.strip_prefix(&self.root_dir).unwrap_or(&full).display().to_string();
+        let bytes = std::fs::read(&full).map_err(|e| format!("Read failed for {}: {}", shown, e))?;
+        text::decode(&bytes, &self.config()).map_err(|e| format!("{} {}", shown, e))
+    }
+
+    /// Studio's text with the project's line-ending policy applied (what gets written and hashed).
+    pub fn normalize_text(&self, content: &str) -> String {
+        text::normalize(content, &self.config()).into_owned()
+    }
+
+    /// Writes text from Studio under the project's encoding policy.
+    pub fn write_text(&self, full_path: &Path, content: &str) -> std::io::Result<()> {
+        let config = self.config();
+        let existing = match config.line_endings {
+            LineEndings::Preserve => std::fs::read(full_path).ok(),
+            LineEndings::Lf => None,
+        };
+        write_atomic(full_path, text::encode(content, existing.as_deref(), &config))
+    }
+
+    fn index_path(root_dir: &Path) -> PathBuf {
+        root_dir.join(INTERNAL_DIR).join(INDEX_FILE)
+    }
+
+    fn load_index(root_dir: &Path) -> (HashMap<String, IndexEntry>, HashMap<PathBuf, String>) {
+        let path = Self::index_path(root_dir);
+        let raw = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(_) => return Default::default(), // First bind
+        };
+        match serde_json::from_str::<PersistedIndex>(&raw) {
+            Ok(mut index) => {
+                // Files deleted while the app was closed no longer hold their paths (nor do hand-edited bad ones)
+                let kept = |path: &Path| safe_path::check_relative(path).is_ok() && root_dir.join(path).exists();
+                index.entries.retain(|_, entry| kept(&entry.path));
+                index.unowned.retain(|path, _| kept(path));
+                println!("RoBezy: Loaded {} index entries from {}", index.entries.len(), path.display());
+                (index.entries, index.unowned)
+            },
+            Err(e) => {
+                eprintln!("RoBezy: Ignoring unreadable index {}: {}", path.display(), e);
+                Default::default()
+            }
+        }
+    }
 
-        // 1. If map already knows this GUID, return existing path
-        if let Some(existing) = state.guid_to_path.get(guid) {
-             // Return existing, assuming no rename logic yet
-             // Check if we need to update extension if usage changes? Unlikely for same GUID.
-             // But wait, if they change Script -> LocalScript, GUID stays same.
-             // We SHOULD update the extension. 
-             // But simplistic approach: Keep same file for now.
-             // (Advanced: Detected ClassName change -> Rename file).
+    /// Persists the GUID <-> path map so bindings survive app restarts and re-registers.
+    pub fn save_index(&self) {
+        let index = {
+            let state = self.state.lock().unwrap();
+            PersistedIndex { version: INDEX_VERSION, entries: state.entries.clone(), unowned: state.unowned.clone() }
+        };
+        let path = Self::index_path(&self.root_dir);
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string_pretty(&index) {
+            Ok(json) => {
+                if let Err(e) = write_atomic(&path, json) {
+                    eprintln!("RoBezy: Failed to save index {}: {}", path.display(), e);
+                }
+            },
+            Err(e) => eprintln!("RoBezy: Failed to serialize index: {}", e),
         }
+    }
 
-        // 2. Calculate ideal relative path
+    /// `save_index` on the blocking pool, for async callers. The Studio sync route calls this once
+    /// per request: `write_file_guid`, `delete_file_guid` and `move_file_guid` don't persist.
+    pub async fn persist_index(&self) {
+        if let Err(e) = self.off_runtime(|fm| fm.save_index()).await {
+            eprintln!("RoBezy: Failed to save index: {}", e);
+        }
+    }
+
+    /// Runs blocking file work on tokio's blocking pool: `write_atomic` fsyncs, which would
+    /// otherwise stall the async runtime (and every other request) on slow disks.
+    pub async fn off_runtime<T: Send + 'static>(&self, f: impl FnOnce(&NativeFileManager) -> T + Send + 'static) -> Result<T, String> {
+        let fm = self.clone();
+        tokio::task::spawn_blocking(move || f(&fm)).await
+            .map_err(|e| format!("Blocking task failed: {}", e))
+    }
+
+    /// The recorded entry for a GUID, if any.
+    pub fn entry(&self, guid: &str) -> Option<IndexEntry> {
+        let state = self.state.lock().unwrap();
+        state.entries.get(guid).cloned()
+    }
+
+    /// ANTI-LOOP: Tell the session watcher to skip events for this relative path for a moment,
+    /// since they are caused by our own write/delete/rename.
+    pub fn suppress(&self, relative_path: &Path) {
+        let key = relative_path.to_string_lossy().replace("\\", "/");
+        self.sourcemap.lock().unwrap().touch(&key);
+        let mut ignores = self.ignore_paths.lock().unwrap();
+        ignores.insert(key, std::time::Instant::now() + SELF_WRITE_IGNORE);
+    }
+
+    /// Marks a path (absolute, or relative to the bound folder) as changed for the sourcemap.
+    /// Paths the backend writes itself are marked by `suppress`.
+    pub fn touch_sourcemap(&self, path: &Path) {
+        let relative = path.strip_prefix(&self.root_dir).unwrap_or(path);
+        self.sourcemap.lock().unwrap().touch(&relative.to_string_lossy());
+    }
+
+    /// Records the instance classes from a workspace snapshot and refreshes `sourcemap.json`.
+    pub fn apply_snapshot(&self, items: &[serde_json::Value]) {
+        self.sourcemap.lock().unwrap().apply_snapshot(items);
+        self.flush_sourcemap();
+    }
+
+    /// Rewrites `sourcemap.json` for everything touched since the last call (if the tree changed).
+    pub fn flush_sourcemap(&self) {
+        let (project, ignore) = {
+            let state = self.state.lock().unwrap();
+            (state.project.clone(), state.ignore.clone())
+        };
+        if let Err(e) = self.sourcemap.lock().unwrap().flush(&self.root_dir, project.as_ref(), &ignore) {
+            eprintln!("RoBezy: {}", e);
+        }
+    }
+
+    /// True if any instance we own a file for lives inside `instance_path` ("A.B" for "A.B.C").
+    fn has_script_descendants(state: &FileManagerState, instance_path: &str) -> bool {
+        let prefix = format!("{}.", instance_path);
+        state.entries.values().any(|e| e.instance_path.starts_with(&prefix))
+    }
+
+    /// Calculates the ideal relative path for an instance ("Workspace.Part.Script" -> "Workspace/Part/Script.server.lua").
+    /// Scripts that contain other scripts use the Rojo folder layout ("Workspace/Part/Script/init.server.lua").
+    /// A script keeps the `.lua` / `.luau` flavour of the file it already has; new ones use the project's choice.
+    fn ideal_path(state: &FileManagerState, guid: &str, instance_path: &str, is_script: bool, class_name: Option<&str>) -> PathBuf {
         let parts: Vec<&str> = instance_path.split('.').collect();
         let mut base_path = PathBuf::new();
         for part in &parts {
-             // Basic Sanitize
-             let safe_part = part.chars()
-                .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
-                .collect::<String>();
-             base_path.push(safe_part);
+             base_path.push(sanitize_segment(part));
         }
         
-        // 3. Apply Extension based on ClassName (Rojo Convention)
-        if is_script {
+        // Apply Extension based on ClassName (Rojo Convention)
+        if let Some(extension) = model::text_extension(class_name) {
+            base_path.set_extension(extension);
+        } else if is_script {
+            if Self::has_script_descendants(state, instance_path) {
+                base_path.push("init");
+            }
+            let ext = state.path_of(guid)
+                .and_then(|p| ScriptExtension::of_path(p))
+                .unwrap_or(state.config.script_extension)
+                .as_str();
             match class_name {
-                Some("Script") => base_path.set_extension("server.lua"),
-                Some("LocalScript") => base_path.set_extension("client.lua"),
-                Some("ModuleScript") => base_path.set_extension("lua"),
-                _ => base_path.set_extension("lua"), // Fallback
+                Some("Script") => base_path.set_extension(format!("server.{}", ext)),
+                Some("LocalScript") => base_path.set_extension(format!("client.{}", ext)),
+                Some("ModuleScript") => base_path.set_extension(ext),
+                _ => base_path.set_extension(ext), // Fallback
             };
         }
-        
-        // 4. Force Assignment (No Suffixes/Renaming Loop)
-        // If multiple instances have the same name, they will fight over the same file.
-        // This is preferable to infinite recursion/file explosion.
-        
-        let final_path = base_path;
+        // A Rojo project decides where the instance lives ("ReplicatedStorage/Shared/X.lua" -> "src/shared/X.lua")
+        match &state.project {
+            Some(project) => PathBuf::from(project.to_disk(&base_path.to_string_lossy())),
+            None => base_path,
+        }
+    }
 
-        // Update maps (Steal ownership)
-        if let Some(old_path) = state.guid_to_path.remove(guid) {
-            if old_path != final_path {
-                state.path_to_guid.remove(&old_path);
+    /// Re-reads `.robezyignore` after it was created, edited or deleted.
+    pub fn reload_ignore(&self) {
+        self.state.lock().unwrap().ignore = IgnoreRules::load(&self.root_dir);
+        self.sourcemap.lock().unwrap().touch("");
+    }
+
+    /// True if `.robezyignore` (or the built-in defaults) exclude this relative path.
+    /// `is_dir` matters for "folder/" patterns; pass what the path was if it no longer exists.
+    pub fn is_excluded(&self, relative_path: &str, is_dir: bool) -> bool {
+        self.state.lock().unwrap().ignore.is_excluded(relative_path, is_dir)
+    }
+
+    /// The absolute path of a relative one, rejected if it could escape the bound folder
+    /// (`..`, absolute paths, reserved names, symlinks pointing outside). Use before every write or delete.
+    pub fn full_path(&self, relative_path: &Path) -> Result<PathBuf, String> {
+        safe_path::resolve(&self.root_dir, relative_path)
+    }
+
+    fn check_included(&self, relative_path: &Path) -> Result<(), String> {
+        let normalized = relative_path.to_string_lossy().replace("\\", "/");
+        if self.is_excluded(&normalized, self.root_dir.join(relative_path).is_dir()) {
+            return Err(format!("{} is excluded by {}", normalized, IGNORE_FILE));
+        }
+        Ok(())
+    }
+
+    /// Bound-folder path -> the Studio path the plugin resolves ("src/shared/X.lua" -> "ReplicatedStorage/Shared/X.lua").
+    /// None for files the project doesn't map to an instance, or that `.robezyignore` excludes.
+    pub fn to_studio_path(&self, relative_path: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        if state.ignore.is_excluded(relative_path, self.root_dir.join(relative_path).is_dir()) {
+            return None;
+        }
+        match &state.project {
+            Some(project) => project.to_studio(relative_path),
+            None => Some(relative_path.to_string()),
+        }
+    }
+
+    /// Studio path -> bound-folder path (the inverse of `to_studio_path`).
+    pub fn to_disk_path(&self, studio_path: &str) -> PathBuf {
+        match &self.state.lock().unwrap().project {
+            Some(project) => PathBuf::from(project.to_disk(studio_path)),
+            None => PathBuf::from(studio_path),
+        }
+    }
+
+    /// Re-reads `default.project.json` after it was created or edited.
+    pub fn reload_project(&self) {
+        let project = Self::load_project(&self.root_dir);
+        self.state.lock().unwrap().project = project;
+        // Every file may map to another instance now
+        self.sourcemap.lock().unwrap().touch("");
+    }
+
+    fn load_project(root_dir: &Path) -> Option<ProjectMap> {
+        let project = ProjectMap::load(root_dir)?;
+        println!("RoBezy: Using {} in {} ({} $path entries)", PROJECT_FILE, root_dir.display(), project.mount_count());
+        Some(project)
+    }
+
+    /// `base` for this GUID, unless another live instance holds it or a file with the same sidecar
+    /// ("Foo.server.lua" and "Foo.lua" both describe themselves in "Foo.meta.json"), then the first
+    /// free variant ("X~2", "X~3", ...). A sibling with the same name (and class, for a shared
+    /// sidecar) holding it was numbered differently before; the plugin's numbering wins.
+    fn claim_path(state: &FileManagerState, guid: &str, instance_path: &str, class_name: Option<&str>, base: &Path) -> PathBuf {
+        let mut n = 1;
+        loop {
+            let candidate = with_duplicate_suffix(base, n);
+            let sidecar = model::sidecar_path(&candidate);
+            let taken = state.path_to_guid.iter().any(|(path, owner)| {
+                if owner == guid || !state.live.contains(owner) || (*path != candidate && model::sidecar_path(path) != sidecar) {
+                    return false;
+                }
+                let Some(entry) = state.entries.get(owner) else { return false };
+                let same_name = without_sibling_number(&entry.instance_path) == without_sibling_number(instance_path);
+                !(same_name && (*path == candidate || entry.class_name.as_deref() == class_name))
+            });
+            if !taken {
+                return candidate;
             }
+            n += 1;
         }
+    }
+
+    /// The GUID that owns a relative path, if any (lets the plugin pick the right sibling).
+    pub fn guid_for_path(&self, relative_path: &Path) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.path_to_guid.get(relative_path).cloned()
+    }
+
+    /// The script a sidecar `.meta.json` belongs to (GUID and ClassName), so Studio applies it
+    /// to that instance and not to another sibling with the same name.
+    pub fn sidecar_owner(&self, sidecar_relative: &Path) -> Option<(String, Option<String>)> {
+        let state = self.state.lock().unwrap();
+        state.entries.iter()
+            .find(|(_, e)| matches!(model::file_kind(&e.path.to_string_lossy()), Some(FileKind::Script(_)))
+                && model::sidecar_path(&e.path) == sidecar_relative)
+            .map(|(guid, e)| (guid.clone(), e.class_name.clone()))
+    }
+
+    /// The file we own for this GUID, or (if we never saw it, e.g. initial connect files)
+    /// the path it would have been written to — unless another instance owns that path.
+    pub fn resolve_path(&self, guid: &str, instance_path: &str, is_script: bool, class_name: Option<&str>) -> Result<PathBuf, String> {
+        let state = self.state.lock().unwrap();
+        if let Some(path) = state.path_of(guid) {
+            return Ok(path.clone());
+        }
+        let guessed = Self::ideal_path(&state, guid, instance_path, is_script, class_name);
+        // Never touch a file that belongs to another instance (e.g. a sibling with the same name)
+        if state.path_to_guid.get(&guessed).map(|owner| owner != guid).unwrap_or(false) {
+            return Err(format!("{} is owned by another instance", guessed.display()));
+        }
+        Ok(guessed)
+    }
+
+    /// Resolves the intended path for a GUID.
+    /// If collision occurs, assigns a suffixed path (e.g. Script~2.lua).
+    /// Returns the RELATIVE path.
+    pub fn assign_path(&self, guid: &str, instance_path: &str, is_script: bool, class_name: Option<&str>) -> Option<PathBuf> {
+        let mut state = self.state.lock().unwrap();
+
+        // 1. A known GUID keeps its path unless the ideal one changed: a new ClassName
+        //    (Script -> LocalScript) gives a new extension. write_file_guid moves the old file.
+
+        // 2. Calculate ideal relative path
+        // 3. Apply Extension based on ClassName (Rojo Convention)
+        let base_path = Self::ideal_path(&state, guid, instance_path, is_script, class_name);
         
-        state.path_to_guid.insert(final_path.clone(), guid.to_string());
-        state.guid_to_path.insert(guid.to_string(), final_path.clone());
-        
+        // 4. Disambiguate siblings with the same name ("Handler~2.server.lua"), keyed by GUID
+        let final_path = Self::claim_path(&state, guid, instance_path, class_name, &base_path);
+
+        // Update maps (Steal ownership)
+        let hash = state.entries.get(guid).and_then(|e| e.hash.clone());
+        state.insert(guid, IndexEntry {
+            path: final_path.clone(),
+            instance_path: instance_path.to_string(),
+            class_name: class_name.map(|c| c.to_string()),
+            hash,
+        });
+        drop(state);
+
+        self.promote_ancestors(&final_path, instance_path);
         Some(final_path)
     }
 
+    /// `assign_path` for a connect: if the GUID owned another file (its sibling number or name
+    /// changed while the app was closed), that file moves along instead of staying behind as a
+    /// disk-only file that would be pushed back to Studio as a duplicate.
+    pub fn reassign_path(&self, guid: &str, instance_path: &str, is_script: bool, class_name: Option<&str>) -> Option<PathBuf> {
+        let previous = self.entry(guid);
+        let relative = self.assign_path(guid, instance_path, is_script, class_name)?;
+        let Some(previous) = previous.filter(|p| p.path != relative) else { return Some(relative) };
+        let (old_full, new_full) = match (self.full_path(&previous.path), self.full_path(&relative)) {
+            (Ok(old_full), Ok(new_full)) => (old_full, new_full),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("RoBezy: Not moving {}: {}", previous.path.display(), e);
+                return Some(relative);
+            },
+        };
+        if old_full.exists() && !new_full.exists() {
+            self.suppress(&previous.path);
+            self.suppress(&relative);
+            if let Some(parent) = new_full.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            match std::fs::rename(&old_full, &new_full) {
+                Ok(()) => {
+                    self.move_sidecar(&previous.path, &relative);
+                    println!("RoBezy: {} is {} now", previous.path.display(), relative.display());
+                },
+                Err(e) => eprintln!("RoBezy: Failed to move {} to {}: {}", previous.path.display(), relative.display(), e),
+            }
+        }
+        Some(relative)
+    }
+
+    /// A file just landed at `relative_path` for `instance_path`: ancestor scripts stored as plain
+    /// files ("A/B.lua" next to "A/B/") move to the folder layout ("A/B/init.lua") so the hierarchy
+    /// round-trips. An ancestor is the script owning the file next to the folder *and* an ancestor
+    /// instance path, so "B~2" keeps its number and never moves into its sibling B's folder.
+    fn promote_ancestors(&self, relative_path: &Path, instance_path: &str) {
+        let promotions: Vec<(String, PathBuf, PathBuf)> = {
+            let state = self.state.lock().unwrap();
+            let mut found = Vec::new();
+            let mut dir = relative_path.parent();
+            while let Some(current) = dir.filter(|d| !d.as_os_str().is_empty()) {
+                dir = current.parent();
+                let Some(dir_name) = current.file_name().map(|n| n.to_string_lossy().to_string()) else { continue };
+                let owner = state.path_to_guid.iter().find(|(path, guid)| {
+                    path.parent() == current.parent()
+                        && path.file_name().map(|n| n.to_string_lossy().split('.').next() == Some(dir_name.as_str())).unwrap_or(false)
+                        && matches!(model::file_kind(&path.to_string_lossy()), Some(FileKind::Script(_)))
+                        && state.entries.get(*guid).map(|e| instance_path.starts_with(&format!("{}.", e.instance_path))).unwrap_or(false)
+                });
+                let Some((old, guid)) = owner else { continue };
+                let file_name = old.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                let target = current.join(format!("init{}", &file_name[dir_name.len()..])); // "B.server.lua" -> "B/init.server.lua"
+                let (ancestor_path, ancestor_class) = state.entries.get(guid)
+                    .map(|e| (e.instance_path.clone(), e.class_name.clone()))
+                    .unwrap_or_default();
+                if Self::claim_path(&state, guid, &ancestor_path, ancestor_class.as_deref(), &target) != target {
+                    eprintln!("RoBezy: Not moving {}: {} belongs to another instance", old.display(), target.display());
+                    continue;
+                }
+                found.push((guid.clone(), old.clone(), target));
+            }
+            found
+        };
+
+        for (guid, old_relative, new_relative) in promotions {
+            let (old_full, new_full) = match (self.full_path(&old_relative), self.full_path(&new_relative)) {
+                (Ok(old_full), Ok(new_full)) => (old_full, new_full),
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("RoBezy: Not moving {}: {}", old_relative.display(), e);
+                    continue;
+                },
+            };
+            self.suppress(&old_relative);
+            self.suppress(&new_relative);
+            if old_full.exists() {
+                if let Some(parent) = new_full.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                if let Err(e) = std::fs::rename(&old_full, &new_full) {
+                    eprintln!("RoBezy: Failed to move {} to {}: {}", old_relative.display(), new_relative.display(), e);
+                    continue;
+                }
+                self.move_sidecar(&old_relative, &new_relative);
+            }
+            println!("RoBezy: {} has children now, moved to {}", old_relative.display(), new_relative.display());
+            let mut state = self.state.lock().unwrap();
+            if let Some(mut entry) = state.entries.get(&guid).cloned() {
+                entry.path = new_relative;
+                state.insert(&guid, entry);
+            }
+        }
+    }
+
+    /// Drops ownership of a relative path (and anything below it, for folders).
+    /// Called when the file disappears from disk so the GUID can be re-assigned later.
+    pub fn forget_path(&self, relative_path: &Path) {
+        if self.release_paths(relative_path) {
+            self.save_index();
+        }
+    }
+
+    /// `forget_path` without persisting. True if anything was owned (or synced) there.
+    fn release_paths(&self, relative_path: &Path) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let unowned = state.unowned.len();
+        state.unowned.retain(|p, _| !p.starts_with(relative_path));
+        let dropped_unowned = state.unowned.len() != unowned;
+        let removed: Vec<PathBuf> = state.path_to_guid.keys()
+            .filter(|p| p.starts_with(relative_path))
+            .cloned()
+            .collect();
+        for path in &removed {
+            state.remove_path(path);
+        }
+        !removed.is_empty() || dropped_unowned
+    }
+
+    /// Re-keys ownership after a file or folder was renamed/moved on disk.
+    /// Everything under a moved folder follows it, so later Studio edits land on the new path.
+    pub fn rename_path(&self, old_relative: &Path, new_relative: &Path) {
+        {
+            let mut state = self.state.lock().unwrap();
+            let moved: Vec<PathBuf> = state.path_to_guid.keys()
+                .chain(state.unowned.keys())
+                .filter(|p| p.starts_with(old_relative))
+                .cloned()
+                .collect();
+            if moved.is_empty() {
+                return;
+            }
+            for old_path in moved {
+                let suffix = old_path.strip_prefix(old_relative).unwrap_or(Path::new(""));
+                let new_path = if suffix.as_os_str().is_empty() {
+                    new_relative.to_path_buf()
+                } else {
+                    new_relative.join(suffix)
+                };
+                if let Some(hash) = state.unowned.remove(&old_path) {
+                    state.unowned.insert(new_path.clone(), hash);
+                }
+                if let Some(guid) = state.path_to_guid.get(&old_path).cloned() {
+                    if let Some(mut entry) = state.entries.get(&guid).cloned() {
+                        entry.path = new_path;
+                        state.insert(&guid, entry);
+                    }
+                }
+            }
+        }
+        self.save_index();
+    }
+
+    /// Renames every script in the bound folder to `target` ("Foo.server.lua" -> "Foo.server.luau"),
+    /// keeps ownership in the index and makes `target` the project's choice for new files.
+    /// Studio is not involved: instance paths don't change. Returns how many files were renamed.
+    pub fn migrate_script_extension(&self, target: ScriptExtension) -> Result<usize, String> {
+        let mut scripts = self.synced_files();
+        scripts.retain(|p| ScriptExtension::of_path(p).is_some());
+
+        let mut renamed = 0;
+        for old_relative in scripts {
+            if ScriptExtension::of_path(&old_relative) == Some(target) {
+                continue;
+            }
+            let new_relative = old_relative.with_extension(target.as_str());
+            let (old_full, new_full) = match (self.full_path(&old_relative), self.full_path(&new_relative)) {
+                (Ok(old_full), Ok(new_full)) => (old_full, new_full),
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("RoBezy: Not migrating {}: {}", old_relative.display(), e);
+                    continue;
+                },
+            };
+            if new_full.exists() {
+                eprintln!("RoBezy: Not migrating {}: {} already exists", old_relative.display(), new_relative.display());
+                continue;
+            }
+            self.suppress(&old_relative);
+            self.suppress(&new_relative);
+            if let Err(e) = std::fs::rename(&old_full, &new_full) {
+                eprintln!("RoBezy: Failed to rename {}: {}", old_relative.display(), e);
+                continue;
+            }
+            self.rename_path(&old_relative, &new_relative);
+            renamed += 1;
+        }
+
+        let mut config = self.config();
+        config.script_extension = target;
+        config.save(&self.root_dir)?;
+        self.state.lock().unwrap().config = config;
+        Ok(renamed)
+    }
+
+    /// Relative paths of every synced file in the bound folder (any `file_kind`) that `.robezyignore`
+    /// doesn't exclude, including ones the project doesn't map.
+    pub fn synced_files(&self) -> Vec<PathBuf> {
+        let ignore = self.state.lock().unwrap().ignore.clone();
+        let mut files = Vec::new();
+        Self::collect_files(&self.root_dir, Path::new(""), &ignore, &mut files);
+        files
+    }
+
+    /// Relative paths of every synced file below `relative_dir` (skips `.robezy/` and excluded paths).
+    fn collect_files(root_dir: &Path, relative_dir: &Path, ignore: &IgnoreRules, out: &mut Vec<PathBuf>) {
+        let entries = match std::fs::read_dir(root_dir.join(relative_dir)) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let relative = relative_dir.join(entry.file_name());
+            if relative == Path::new(INTERNAL_DIR) {
+                continue;
+            }
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if ignore.is_excluded(&relative.to_string_lossy(), is_dir) {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => Self::collect_files(root_dir, &relative, ignore, out),
+                Ok(t) if t.is_file() && model::file_kind(&relative.to_string_lossy()).is_some() => out.push(relative),
+                _ => {},
+            }
+        }
+    }
+
     /// Writes content to a file using the assigned path.
-    pub async fn write_file_guid(&self, guid: &str, instance_path: &str, is_script: bool, class_name: Option<&str>, content: String) -> Result<PathBuf, String> {
+    /// If the GUID already owned a different file (e.g. its ClassName changed), that file is
+    /// renamed first so the instance never ends up with two files.
+    /// Does not persist the index; call `persist_index` after the batch.
+    pub async fn write_file_guid(&self, guid: &str, instance_path: &str, is_script: bool, class_name: Option<&str>, content: String, meta: &ScriptMeta) -> Result<WriteOutcome, String> {
+        let content = self.normalize_text(&content);
+        let ideal = {
+            let state = self.state.lock().unwrap();
+            Self::ideal_path(&state, guid, instance_path, is_script, class_name)
+        };
+        // Reject bad instance names ("..", "CON") before the path is claimed in the index
+        safe_path::check_relative(&ideal)?;
+        self.check_included(&ideal)?;
+        let previous = self.entry(guid);
         let relative_path = self.assign_path(guid, instance_path, is_script, class_name)
             .ok_or("Failed to buffer path")?;
             
-        let final_path = self.root_dir.join(&relative_path);
-        
-        // Security check
-        if !final_path.starts_with(&self.root_dir) {
-            return Err("Access Denied".to_string());
-        }
+        let final_path = self.full_path(&relative_path)?;
+
+        self.suppress(&relative_path);
 
         if let Some(parent) = final_path.parent() {
             fs::create_dir_all(parent).await
                 .map_err(|e| format!("Dirs failed: {}", e))?;
         }
 
-        fs::write(&final_path, content).await
+        let mut class_change = None;
+        if let Some(previous) = previous.filter(|p| p.path != relative_path) {
+            let old_full = self.full_path(&previous.path)?;
+            if fs::metadata(&old_full).await.is_ok() {
+                // Single rename: the watcher sees both ends suppressed and never echoes it to Studio
+                self.suppress(&previous.path);
+                fs::rename(&old_full, &final_path).await
+                    .map_err(|e| format!("Rename failed: {}", e))?;
+                self.move_sidecar(&previous.path, &relative_path);
+                if let Some(parent) = previous.path.parent() {
+                    self.prune_empty_dirs(parent).await;
+                }
+            }
+            if is_script && previous.class_name.is_some() && previous.class_name.as_deref() != class_name {
+                class_change = Some(ClassChange {
+                    old_path: previous.path,
+                    old_class: previous.class_name,
+                    new_class: class_name.map(|c| c.to_string()),
+                });
+            }
+        }
+
+        let written = content.clone();
+        self.off_runtime(move |fm| fm.write_text(&final_path, &written)).await?
             .map_err(|e| format!("Write failed: {}", e))?;
-            
+        self.mark_synced(&relative_path, &content);
+
+        let (script, meta) = (relative_path.clone(), meta.clone());
+        if let Err(e) = self.off_runtime(move |fm| fm.write_sidecar(&script, &meta)).await.and_then(|r| r) {
+            eprintln!("RoBezy: Failed to write metadata for {}: {}", relative_path.display(), e);
+        }
+        Ok(WriteOutcome { path: relative_path, class_change })
+    }
+
+    /// Merges Studio-reported attributes/tags/Enabled/RunContext into the script's sidecar `.meta.json`
+    /// (keeping anything else an agent put there). Removes the sidecar once it is empty.
+    pub fn write_sidecar(&self, script_relative: &Path, meta: &ScriptMeta) -> Result<(), String> {
+        if !meta.is_reported() {
+            return Ok(());
+        }
+        let relative = model::sidecar_path(script_relative);
+        let full = self.full_path(&relative)?;
+
+        let existing = std::fs::read_to_string(&full).ok();
+        let mut desc: InstanceDescription = existing.as_deref()
+            .and_then(|raw| serde_json::from_str(raw).ok())
+            .unwrap_or_default();
+        let before = serde_json::to_value(&desc).ok();
+        meta.apply_to(&mut desc);
+        if existing.is_some() && serde_json::to_value(&desc).ok() == before {
+            return Ok(()); // Unchanged: don't touch the file
+        }
+
+        self.suppress(&relative);
+        if desc.is_empty() {
+            if existing.is_some() {
+                std::fs::remove_file(&full).map_err(|e| format!("Delete failed: {}", e))?;
+            }
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(&desc).map_err(|e| e.to_string())?;
+        write_atomic(&full, json + "\n").map_err(|e| format!("Write failed: {}", e))
+    }
+
+    /// Keeps a script's sidecar `.meta.json` next to it when the script file moves.
+    fn move_sidecar(&self, old_script: &Path, new_script: &Path) {
+        let old_relative = model::sidecar_path(old_script);
+        let new_relative = model::sidecar_path(new_script);
+        let (old_full, new_full) = match (self.full_path(&old_relative), self.full_path(&new_relative)) {
+            (Ok(old_full), Ok(new_full)) => (old_full, new_full),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("RoBezy: Not moving {}: {}", old_relative.display(), e);
+                return;
+            },
+        };
+        if old_relative == new_relative || !old_full.exists() {
+            return;
+        }
+        self.suppress(&old_relative);
+        self.suppress(&new_relative);
+        if let Some(parent) = new_full.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(e) = std::fs::rename(&old_full, &new_full) {
+            eprintln!("RoBezy: Failed to move {}: {}", old_relative.display(), e);
+        }
+    }
+
+    /// Records `content` as what Studio and disk agree on for this path. Without a GUID owner
+    /// (a file created on disk) only the hash is kept, by path, until an entry claims it.
+    /// Does not persist; call `save_index` after a batch.
+    pub fn mark_synced(&self, relative_path: &Path, content: &str) {
+        let mut state = self.state.lock().unwrap();
+        match state.path_to_guid.get(relative_path).cloned() {
+            Some(guid) => {
+                if let Some(entry) = state.entries.get_mut(&guid) {
+                    entry.hash = Some(content_hash(content));
+                }
+                state.synced_text.insert(guid, content.to_string());
+            },
+            None => {
+                state.unowned.insert(relative_path.to_path_buf(), content_hash(content));
+            },
+        }
+    }
+
+    /// The content Studio last confirmed for this path, if it was synced since the app started
+    /// (only its hash survives restarts).
+    pub fn synced_content(&self, relative_path: &Path) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        let guid = state.path_to_guid.get(relative_path)?;
+        state.synced_text.get(guid).cloned()
+    }
+
+    /// Hash of the content Studio and disk last agreed on for this path, if it was ever synced.
+    pub fn synced_hash(&self, relative_path: &Path) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        match state.path_to_guid.get(relative_path) {
+            Some(guid) => state.entries.get(guid).and_then(|e| e.hash.clone()),
+            None => state.unowned.get(relative_path).cloned(),
+        }
+    }
+
+    /// Three-way check for an incoming Studio write: true when both Studio and disk moved away
+    /// from the last synced content (and didn't happen to land on the same text).
+    pub fn diverged(&self, relative_path: &Path, studio_content: &str) -> bool {
+        let base = match self.synced_hash(relative_path) {
+            Some(base) => base,
+            None => return false, // Never synced: nothing to compare against
+        };
+        let disk = match self.read_text(relative_path) {
+            Ok(disk) => disk,
+            Err(_) => return false,
+        };
+        let disk_hash = content_hash(&disk);
+        let studio_hash = content_hash(&self.normalize_text(studio_content));
+        disk_hash != base && studio_hash != base && disk_hash != studio_hash
+    }
+
+    /// Reads a text file in the bound folder (see `read_text`).
+    pub async fn read_relative(&self, relative_path: &Path) -> Result<String, String> {
+        let full = self.full_path(relative_path)?;
+        self.read_text(&full)
+    }
+
+    /// Writes a file that is not owned by an instance (e.g. ".conflict" copies) without waking the watcher.
+    /// Same encoding policy as Studio writes.
+    pub async fn write_relative(&self, relative_path: &Path, content: &str) -> Result<(), String> {
+        let full = self.full_path(relative_path)?;
+        self.suppress(relative_path);
+        if let Some(parent) = full.parent() {
+            fs::create_dir_all(parent).await
+                .map_err(|e| format!("Dirs failed: {}", e))?;
+        }
+        let content = content.to_string();
+        self.off_runtime(move |fm| fm.write_text(&full, &content)).await?
+            .map_err(|e| format!("Write failed: {}", e))
+    }
+
+    /// Removes a file by relative path without waking the watcher. Missing files are fine.
+    pub async fn remove_relative(&self, relative_path: &Path) -> Result<(), String> {
+        let full = self.full_path(relative_path)?;
+        self.suppress(relative_path);
+        match fs::remove_file(&full).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Delete failed: {}", e)),
+        }
+    }
+
+    /// Studio destroyed the instance: remove the file we own for it and prune
+    /// any folders left empty. Returns the RELATIVE path that was removed.
+    /// Does not persist the index; call `persist_index` after the batch.
+    pub async fn delete_file_guid(&self, guid: &str, instance_path: &str, is_script: bool, class_name: Option<&str>) -> Result<PathBuf, String> {
+        let relative_path = self.resolve_path(guid, instance_path, is_script, class_name)?;
+        self.check_included(&relative_path)?;
+        let final_path = self.full_path(&relative_path)?;
+
+        self.release_paths(&relative_path);
+        self.suppress(&relative_path);
+
+        match fs::remove_file(&final_path).await {
+            Ok(_) => {},
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => return Err(format!("Delete failed: {}", e)),
+        }
+        // The script's sidecar metadata goes with it
+        if is_script || model::text_extension(class_name).is_some() {
+            self.remove_relative(&model::sidecar_path(&relative_path)).await?;
+        }
+
+        if let Some(parent) = relative_path.parent() {
+            self.demote_folder_script(parent).await;
+            self.prune_empty_dirs(parent).await;
+        }
         Ok(relative_path)
     }
+
+    /// Studio renamed or reparented the instance: move the file we own for it
+    /// instead of writing a second copy. Returns the new RELATIVE path.
+    /// Does not persist the index; call `persist_index` after the batch.
+    pub async fn move_file_guid(&self, guid: &str, old_instance_path: Option<&str>, instance_path: &str, is_script: bool, class_name: Option<&str>) -> Result<PathBuf, String> {
+        let old_instance_path = old_instance_path.unwrap_or(instance_path);
+        let old_relative = self.resolve_path(guid, old_instance_path, is_script, class_name)?;
+        let new_relative = {
+            let state = self.state.lock().unwrap();
+            Self::claim_path(&state, guid, instance_path, class_name, &Self::ideal_path(&state, guid, instance_path, is_script, class_name))
+        };
+        self.check_included(&old_relative)?;
+        self.check_included(&new_relative)?;
+
+        let old_full = self.full_path(&old_relative)?;
+        let new_full = self.full_path(&new_relative)?;
+
+        if old_relative == new_relative {
+            return Ok(new_relative);
+        }
+        if fs::metadata(&old_full).await.is_err() {
+            return Err(format!("Source missing: {}", old_relative.display()));
+        }
+
+        self.suppress(&old_relative);
+        self.suppress(&new_relative);
+
+        if let Some(parent) = new_full.parent() {
+            fs::create_dir_all(parent).await
+                .map_err(|e| format!("Dirs failed: {}", e))?;
+        }
+        fs::rename(&old_full, &new_full).await
+            .map_err(|e| format!("Rename failed: {}", e))?;
+        self.move_sidecar(&old_relative, &new_relative);
+
+        {
+            let mut state = self.state.lock().unwrap();
+            let hash = state.entries.get(guid).and_then(|e| e.hash.clone());
+            state.remove_path(&old_relative);
+            state.insert(guid, IndexEntry {
+                path: new_relative.clone(),
+                instance_path: instance_path.to_string(),
+                class_name: class_name.map(|c| c.to_string()),
+                hash,
+            });
+        }
+        self.promote_ancestors(&new_relative, instance_path);
+
+        if let Some(parent) = old_relative.parent() {
+            self.demote_folder_script(parent).await;
+            self.prune_empty_dirs(parent).await;
+        }
+        Ok(new_relative)
+    }
+
+    /// The inverse of `promote_ancestors`: once a folder script's init file is all that is left in
+    /// its folder ("A/B/init.lua", maybe with "init.meta.json"), it goes back to a plain file ("A/B.lua").
+    async fn demote_folder_script(&self, relative_dir: &Path) {
+        let Some(dir_name) = relative_dir.file_name().map(|n| n.to_string_lossy().to_string()) else { return };
+        let Ok(full_dir) = self.full_path(relative_dir) else { return };
+        let Ok(mut entries) = fs::read_dir(&full_dir).await else { return };
+        let mut names = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+        if names.iter().any(|n| !n.starts_with("init.")) {
+            return;
+        }
+        let Some((guid, old_relative)) = ({
+            let state = self.state.lock().unwrap();
+            names.iter()
+                .map(|n| relative_dir.join(n))
+                .filter(|p| matches!(model::file_kind(&p.to_string_lossy()), Some(FileKind::Script(_))))
+                .find_map(|p| state.path_to_guid.get(&p).map(|guid| (guid.clone(), p)))
+        }) else {
+            return;
+        };
+        let file_name = old_relative.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let new_relative = relative_dir.with_file_name(format!("{}{}", dir_name, &file_name["init".len()..]));
+        // A project mount root ("src/shared/init.lua") has to stay a folder
+        if self.to_studio_path(&new_relative.to_string_lossy()).is_none() {
+            return;
+        }
+        let (Ok(old_full), Ok(new_full)) = (self.full_path(&old_relative), self.full_path(&new_relative)) else { return };
+        if fs::metadata(&new_full).await.is_ok() {
+            eprintln!("RoBezy: Not moving {}: {} already exists", old_relative.display(), new_relative.display());
+            return;
+        }
+        self.suppress(&old_relative);
+        self.suppress(&new_relative);
+        if let Err(e) = fs::rename(&old_full, &new_full).await {
+            eprintln!("RoBezy: Failed to move {} to {}: {}", old_relative.display(), new_relative.display(), e);
+            return;
+        }
+        self.move_sidecar(&old_relative, &new_relative);
+        println!("RoBezy: {} has no children left, moved to {}", old_relative.display(), new_relative.display());
+        {
+            let mut state = self.state.lock().unwrap();
+            if let Some(mut entry) = state.entries.get(&guid).cloned() {
+                entry.path = new_relative;
+                state.insert(&guid, entry);
+            }
+        }
+    }
+
+    /// Walks up from `relative_dir` removing folders that became empty (never the root itself).
+    async fn prune_empty_dirs(&self, relative_dir: &Path) {
+        let mut current = relative_dir.to_path_buf();
+        while !current.as_os_str().is_empty() {
+            let Ok(full) = self.full_path(&current) else { break };
+            let is_empty = match fs::read_dir(&full).await {
+                Ok(mut entries) => matches!(entries.next_entry().await, Ok(None)),
+                Err(_) => false,
+            };
+            if !is_empty {
+                break;
+            }
+            self.suppress(&current);
+            if fs::remove_dir(&full).await.is_err() {
+                break;
+            }
+            current = match current.parent() {
+                Some(p) => p.to_path_buf(),
+                None => break,
+            };
+        }
+    }
 }
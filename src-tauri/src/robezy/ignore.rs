@@ -0,0 +1,257 @@
+use std::path::Path;
+
+/// Gitignore-style exclusions, kept in the bound folder. Excluded files are never
+/// synced in either direction and don't appear in the sourcemap.
+pub const IGNORE_FILE: &str = ".robezyignore";
+
+// Applied before `.robezyignore`, which can re-include any of them with "!pattern".
+const DEFAULT_PATTERNS: &[&str] = &[
+    // Version control and tooling
+    ".git/",
+    ".hg/",
+    ".svn/",
+    "node_modules/",
+    // Vim: swap files and the "4913" write test
+    "*.swp",
+    "*.swo",
+    "*.swx",
+    "4913",
+    // Emacs: backups, autosaves and lock files
+    "*~",
+    "\\#*#",
+    ".#*",
+    // JetBrains safe-write and Kate swap files
+    "*___jb_tmp___",
+    "*___jb_old___",
+    "*.kate-swp",
+    // Generic temp / backup / merge leftovers
+    "*.tmp",
+    "*.temp",
+    "*.bak",
+    "*.orig",
+    "*.rej",
+    // OS metadata
+    ".DS_Store",
+    "._*",
+    "Thumbs.db",
+    "desktop.ini",
+];
+
+#[derive(Debug, Clone)]
+struct Rule {
+    segments: Vec<String>, // Pattern split on "/"; "**" matches any number of segments
+    negate: bool,   // "!pattern" re-includes
+    dir_only: bool, // "pattern/" only matches folders
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let mut pattern = line.trim_end();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return None;
+        }
+        let negate = pattern.starts_with('!');
+        if negate || pattern.starts_with("\\#") || pattern.starts_with("\\!") {
+            pattern = &pattern[1..];
+        }
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        // A slash anywhere but the end anchors the pattern to the folder root
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+        let mut segments: Vec<String> = Vec::new();
+        if !anchored {
+            segments.push("**".to_string());
+        }
+        segments.extend(pattern.split('/').filter(|s| !s.is_empty()).map(|s| s.to_string()));
+        Some(Self { segments, negate, dir_only })
+    }
+
+    fn matches(&self, path: &[&str], is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && match_segments(&self.segments, path)
+    }
+}
+
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len()).any(|i| match_segments(rest, &path[i..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => wildcard(first.as_bytes(), segment.as_bytes()) && match_segments(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+/// `*`, `?`, `[abc]` / `[a-z]` / `[!x]` and `\` escapes within one path segment.
+fn wildcard(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') => (0..=text.len()).any(|i| wildcard(&pattern[1..], &text[i..])),
+        Some(b'?') => !text.is_empty() && wildcard(&pattern[1..], &text[1..]),
+        Some(b'[') => {
+            let Some((&c, text_rest)) = text.split_first() else { return false };
+            let mut i = 1;
+            let negate = matches!(pattern.get(i), Some(b'!') | Some(b'^'));
+            if negate {
+                i += 1;
+            }
+            let mut matched = false;
+            let mut first = true;
+            while i < pattern.len() && (pattern[i] != b']' || first) {
+                first = false;
+                if i + 2 < pattern.len() && pattern[i + 1] == b'-' && pattern[i + 2] != b']' {
+                    matched |= pattern[i] <= c && c <= pattern[i + 2];
+                    i += 3;
+                } else {
+                    matched |= pattern[i] == c;
+                    i += 1;
+                }
+            }
+            if i >= pattern.len() {
+                // No closing "]": treat "[" literally
+                return c == b'[' && wildcard(&pattern[1..], text_rest);
+            }
+            matched != negate && wildcard(&pattern[i + 1..], text_rest)
+        },
+        Some(b'\\') if pattern.len() > 1 => text.first() == Some(&pattern[1]) && wildcard(&pattern[2..], &text[1..]),
+        Some(&p) => text.first() == Some(&p) && wildcard(&pattern[1..], &text[1..]),
+    }
+}
+
+/// The built-in defaults followed by the folder's `.robezyignore`. Later rules win, like git.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Reads `.robezyignore` from a bound folder (defaults only if there is none).
+    pub fn load(root_dir: &Path) -> Self {
+        let mut rules = Self::default().rules;
+        if let Ok(raw) = std::fs::read_to_string(root_dir.join(IGNORE_FILE)) {
+            let before = rules.len();
+            rules.extend(raw.lines().filter_map(Rule::parse));
+            println!("RoBezy: Loaded {} rules from {} in {}", rules.len() - before, IGNORE_FILE, root_dir.display());
+        }
+        Self { rules }
+    }
+
+    /// True if the relative path (or a folder containing it) is excluded.
+    /// `is_dir` says whether the path itself is a folder (unknown for deleted paths: pass false).
+    pub fn is_excluded(&self, relative_path: &str, is_dir: bool) -> bool {
+        let normalized = relative_path.replace('\\', "/");
+        let segments: Vec<&str> = normalized.split('/').filter(|s| !s.is_empty()).collect();
+        // As in git, nothing inside an excluded folder can be re-included
+        for depth in 1..=segments.len() {
+            let last = depth == segments.len();
+            if self.decide(&segments[..depth], !last || is_dir) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn decide(&self, path: &[&str], is_dir: bool) -> bool {
+        self.rules.iter().rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .map(|rule| !rule.negate)
+            .unwrap_or(false)
+    }
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self { rules: DEFAULT_PATTERNS.iter().filter_map(|p| Rule::parse(p)).collect() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The defaults followed by `lines`, as if they were the folder's `.robezyignore`
+    fn with_lines(lines: &str) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        rules.rules.extend(lines.lines().filter_map(Rule::parse));
+        rules
+    }
+
+    #[test]
+    fn unanchored_patterns_match_at_any_depth() {
+        let rules = with_lines("Secret.lua");
+        assert!(rules.is_excluded("Secret.lua", false));
+        assert!(rules.is_excluded("ServerScriptService/Admin/Secret.lua", false));
+        assert!(!rules.is_excluded("ServerScriptService/NotSecret.lua", false));
+    }
+
+    #[test]
+    fn anchored_patterns_match_from_the_root_only() {
+        let leading_slash = with_lines("/Secret.lua");
+        assert!(leading_slash.is_excluded("Secret.lua", false));
+        assert!(!leading_slash.is_excluded("Admin/Secret.lua", false));
+
+        let inner_slash = with_lines("Admin/Secret.lua");
+        assert!(inner_slash.is_excluded("Admin/Secret.lua", false));
+        assert!(!inner_slash.is_excluded("ServerScriptService/Admin/Secret.lua", false));
+    }
+
+    #[test]
+    fn trailing_slash_only_matches_folders() {
+        let rules = with_lines("Generated/");
+        assert!(rules.is_excluded("Generated", true));
+        assert!(rules.is_excluded("ReplicatedStorage/Generated/Types.lua", false));
+        assert!(!rules.is_excluded("Generated", false));
+    }
+
+    #[test]
+    fn double_star_matches_any_number_of_folders() {
+        let leading = with_lines("**/Tests");
+        assert!(leading.is_excluded("Tests/Foo.lua", false));
+        assert!(leading.is_excluded("ServerScriptService/Lib/Tests/Foo.lua", false));
+
+        let middle = with_lines("ServerScriptService/**/Spec.lua");
+        assert!(middle.is_excluded("ServerScriptService/Spec.lua", false));
+        assert!(middle.is_excluded("ServerScriptService/A/B/Spec.lua", false));
+        assert!(!middle.is_excluded("ReplicatedStorage/A/Spec.lua", false));
+    }
+
+    #[test]
+    fn negation_re_includes_files() {
+        let rules = with_lines("*.lua\n!Keep.lua");
+        assert!(rules.is_excluded("ServerScriptService/Foo.lua", false));
+        assert!(!rules.is_excluded("ServerScriptService/Keep.lua", false));
+        // Defaults can be re-included too
+        assert!(!with_lines("*.bak\n!Notes.bak").is_excluded("Notes.bak", false));
+    }
+
+    #[test]
+    fn negation_cannot_re_include_inside_an_excluded_folder() {
+        let rules = with_lines("Vendor/\n!Vendor/Keep.lua");
+        assert!(rules.is_excluded("Vendor/Keep.lua", false));
+        assert!(rules.is_excluded("Vendor/Other.lua", false));
+    }
+
+    #[test]
+    fn negated_character_classes() {
+        let rules = with_lines("Test[!s].lua");
+        assert!(rules.is_excluded("TestA.lua", false));
+        assert!(!rules.is_excluded("Tests.lua", false));
+        assert!(!rules.is_excluded("Test.lua", false));
+        assert!(with_lines("Test[a-c].lua").is_excluded("Testb.lua", false));
+    }
+
+    #[test]
+    fn escaped_hash_is_a_pattern_not_a_comment() {
+        // The built-in "\#*#" default catches Emacs autosaves
+        let defaults = IgnoreRules::default();
+        assert!(defaults.is_excluded("ServerScriptService/#Foo.lua#", false));
+        assert!(!defaults.is_excluded("ServerScriptService/Foo.lua", false));
+        // Unescaped, it is a comment and matches nothing
+        assert!(Rule::parse("#Foo.lua").is_none());
+        assert!(with_lines("\\#Foo.lua").is_excluded("#Foo.lua", false));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+
+// LocalizationTable <-> `.csv`, in the layout Studio's "Export CSV" produces:
+// a header of Key, Source, Context, Example followed by one column per locale ("es", "pt-br", ...).
+
+/// Splits CSV text into rows of fields (quoted fields, `""` escapes, `\n` or `\r\n` line ends).
+pub fn parse_rows(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            } else {
+                field.push(c);
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '"' => return Err(format!("row {}: unexpected quote inside an unquoted field", rows.len() + 1)),
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {},
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            },
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("row {}: unterminated quoted field", rows.len() + 1));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    // Blank lines carry no entry
+    rows.retain(|r| !(r.len() == 1 && r[0].is_empty()));
+    Ok(rows)
+}
+
+/// Checks a table before it is sent to Studio, which rejects the whole table
+/// (or silently drops entries) when rows are malformed or keys repeat.
+pub fn check(normalized_path: &str, content: &str) -> Result<(), String> {
+    let rows = parse_rows(content).map_err(|e| format!("{}: {}", normalized_path, e))?;
+    let mut rows = rows.into_iter();
+    let header = rows.next()
+        .ok_or_else(|| format!("{}: missing header row", normalized_path))?;
+
+    let mut columns = HashSet::new();
+    for name in &header {
+        if name.trim().is_empty() {
+            return Err(format!("{}: header has an empty column name", normalized_path));
+        }
+        if !columns.insert(name.as_str()) {
+            return Err(format!("{}: duplicate column \"{}\"", normalized_path, name));
+        }
+    }
+    if !columns.contains("Key") && !columns.contains("Source") {
+        return Err(format!("{}: header needs a \"Key\" or \"Source\" column", normalized_path));
+    }
+    let index_of = |name: &str| header.iter().position(|c| c == name);
+    let (key, source, context) = (index_of("Key"), index_of("Source"), index_of("Context"));
+
+    let mut seen = HashSet::new();
+    for (i, row) in rows.enumerate() {
+        let row_number = i + 2; // 1-based, after the header
+        if row.len() != header.len() {
+            return Err(format!("{}: row {} has {} fields, expected {}", normalized_path, row_number, row.len(), header.len()));
+        }
+        let field = |idx: Option<usize>| idx.map(|i| row[i].as_str()).unwrap_or("");
+        // Entries are identified by Key, or by Source + Context when they have no key
+        let identity = if !field(key).is_empty() {
+            format!("key:{}", field(key))
+        } else if !field(source).is_empty() {
+            format!("source:{}\u{0}{}", field(source), field(context))
+        } else {
+            return Err(format!("{}: row {} needs a Key or a Source", normalized_path, row_number));
+        };
+        if !seen.insert(identity) {
+            return Err(format!("{}: row {} repeats an earlier entry", normalized_path, row_number));
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,16 @@
 pub mod session;
 pub mod server;
 pub mod fs;
+pub mod conflict;
+pub mod model;
+pub mod localization;
+pub mod config;
+pub mod project;
+pub mod sourcemap;
+pub mod reconcile;
+pub mod diff;
+pub mod preview;
+pub mod ignore;
+pub mod text;
+pub mod upload;
+pub mod persist;
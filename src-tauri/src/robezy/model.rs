@@ -0,0 +1,193 @@
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+// Rojo-compatible instance descriptions.
+// `Foo.model.json` declares a whole instance (and its children) named `Foo`.
+// `Foo.meta.json` adds properties/attributes to the instance `Foo` next to it,
+// and `Dir/init.meta.json` does the same for the folder `Dir` (and may change its class).
+
+/// What a synced file describes, decided by its name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileKind {
+    Script(&'static str), // ClassName
+    Text(&'static str),   // ClassName of an instance whose file is its text value
+    Model,
+    Meta,
+}
+
+/// .server.lua / .server.luau -> Script
+/// .client.lua / .client.luau -> LocalScript
+/// .lua / .luau -> ModuleScript
+/// .txt -> StringValue (Value)
+/// .csv -> LocalizationTable (entries)
+/// .model.json / .meta.json -> instance descriptions
+/// Anything else (including plain .json) is not synced.
+pub fn file_kind(normalized_path: &str) -> Option<FileKind> {
+    let script_stem = normalized_path.strip_suffix(".luau")
+        .or_else(|| normalized_path.strip_suffix(".lua"));
+    if let Some(stem) = script_stem {
+        if stem.ends_with(".server") {
+            Some(FileKind::Script("Script"))
+        } else if stem.ends_with(".client") {
+            Some(FileKind::Script("LocalScript"))
+        } else {
+            Some(FileKind::Script("ModuleScript"))
+        }
+    } else if normalized_path.ends_with(".txt") {
+        Some(FileKind::Text("StringValue"))
+    } else if normalized_path.ends_with(".csv") {
+        Some(FileKind::Text("LocalizationTable"))
+    } else if normalized_path.ends_with(".model.json") {
+        Some(FileKind::Model)
+    } else if normalized_path.ends_with(".meta.json") {
+        Some(FileKind::Meta)
+    } else {
+        None
+    }
+}
+
+/// Extension for the non-script classes stored as plain text files.
+pub fn text_extension(class_name: Option<&str>) -> Option<&'static str> {
+    match class_name {
+        Some("StringValue") => Some("txt"),
+        Some("LocalizationTable") => Some("csv"),
+        _ => None,
+    }
+}
+
+/// One instance in a `.model.json` / `.meta.json` file (Rojo field names, camelCase or PascalCase).
+/// Property and attribute values are kept as JSON; the plugin converts them using the
+/// property's current type or an explicit `{ "Vector3": [..] }` style wrapper.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDescription {
+    #[serde(default, alias = "Name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, alias = "ClassName", skip_serializing_if = "Option::is_none")]
+    pub class_name: Option<String>,
+    #[serde(default, alias = "Properties", skip_serializing_if = "Map::is_empty")]
+    pub properties: Map<String, Value>,
+    #[serde(default, alias = "Attributes", skip_serializing_if = "Map::is_empty")]
+    pub attributes: Map<String, Value>,
+    #[serde(default, alias = "Children", skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<InstanceDescription>,
+    #[serde(default, alias = "IgnoreUnknownInstances", skip_serializing_if = "Option::is_none")]
+    pub ignore_unknown_instances: Option<bool>,
+}
+
+/// Parses and checks a description before it is sent to Studio.
+/// Returns a readable error for broken files so they never reach the plugin.
+pub fn parse(kind: FileKind, normalized_path: &str, content: &str) -> Result<InstanceDescription, String> {
+    let desc: InstanceDescription = serde_json::from_str(content)
+        .map_err(|e| format!("{}: invalid JSON ({})", normalized_path, e))?;
+    match kind {
+        FileKind::Model => {
+            if desc.class_name.as_deref().unwrap_or("").is_empty() {
+                return Err(format!("{}: model files need a \"className\"", normalized_path));
+            }
+            check_children(normalized_path, &desc.children)?;
+        },
+        FileKind::Meta => {
+            if !desc.children.is_empty() {
+                return Err(format!("{}: meta files cannot declare \"children\"", normalized_path));
+            }
+            let is_init = normalized_path.rsplit('/').next() == Some("init.meta.json");
+            if desc.class_name.is_some() && !is_init {
+                return Err(format!("{}: \"className\" is only allowed in init.meta.json", normalized_path));
+            }
+        },
+        FileKind::Script(_) | FileKind::Text(_) => {},
+    }
+    Ok(desc)
+}
+
+fn check_children(normalized_path: &str, children: &[InstanceDescription]) -> Result<(), String> {
+    for child in children {
+        if child.name.as_deref().unwrap_or("").is_empty() || child.class_name.as_deref().unwrap_or("").is_empty() {
+            return Err(format!("{}: every child needs a \"name\" and a \"className\"", normalized_path));
+        }
+        check_children(normalized_path, &child.children)?;
+    }
+    Ok(())
+}
+
+/// Instance data reported with a script write that lives in its sidecar `.meta.json`.
+/// `None` means "not reported" (e.g. an older plugin) and leaves the sidecar alone;
+/// an empty value (or a default like `Enabled: true`) clears it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "object_or_empty_array")]
+    pub attributes: Option<Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>, // Script / LocalScript only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_context: Option<String>, // Script only ("Legacy", "Server", "Client", "Plugin")
+}
+
+impl ScriptMeta {
+    /// Merges the reported fields into an existing description (other fields are kept).
+    pub fn apply_to(&self, desc: &mut InstanceDescription) {
+        if let Some(attributes) = &self.attributes {
+            desc.attributes = attributes.clone();
+        }
+        if let Some(tags) = &self.tags {
+            if tags.is_empty() {
+                desc.properties.remove("Tags");
+            } else {
+                desc.properties.insert("Tags".to_string(), Value::from(tags.clone()));
+            }
+        }
+        if let Some(enabled) = self.enabled {
+            desc.properties.remove("Disabled"); // Older Rojo projects use the inverted property
+            if enabled {
+                desc.properties.remove("Enabled");
+            } else {
+                desc.properties.insert("Enabled".to_string(), Value::Bool(false));
+            }
+        }
+        if let Some(run_context) = &self.run_context {
+            if run_context == "Legacy" {
+                desc.properties.remove("RunContext");
+            } else {
+                desc.properties.insert("RunContext".to_string(), Value::from(run_context.clone()));
+            }
+        }
+    }
+
+    pub fn is_reported(&self) -> bool {
+        self.attributes.is_some() || self.tags.is_some() || self.enabled.is_some() || self.run_context.is_some()
+    }
+}
+
+// Roblox's JSONEncode turns an empty table into `[]`, so an empty attribute map arrives as an array
+fn object_or_empty_array<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Map<String, Value>>, D::Error> {
+    match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Object(map)) => Ok(Some(map)),
+        Some(Value::Array(items)) if items.is_empty() => Ok(Some(Map::new())),
+        Some(other) => Err(serde::de::Error::custom(format!("expected an object, got {}", other))),
+    }
+}
+
+/// "A/Foo~2.server.lua" -> "A/Foo~2.meta.json", "A/Foo/init.lua" -> "A/Foo/init.meta.json"
+pub fn sidecar_path(script_relative: &Path) -> PathBuf {
+    let file = script_relative.file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = file.split('.').next().unwrap_or(&file);
+    script_relative.with_file_name(format!("{}.meta.json", stem))
+}
+
+impl InstanceDescription {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.class_name.is_none()
+            && self.properties.is_empty()
+            && self.attributes.is_empty()
+            && self.children.is_empty()
+            && self.ignore_unknown_instances.is_none()
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::robezy::conflict::Conflict;
+use crate::robezy::session::{FileChange, SessionIdentity};
+
+// Sessions are kept in the app-data folder so a restarted app picks up where it stopped:
+// same session ids, same bindings, and the changes Studio hadn't acknowledged yet, with
+// their sequence numbers, so a plugin that kept polling through the restart just carries on.
+
+pub const STORE_FILE: &str = "sessions.json";
+/// Bumped when the layout changes; a store from another version is ignored.
+const STORE_VERSION: u32 = 1;
+
+/// What a session needs to come back after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedSession {
+    pub identity: SessionIdentity,
+    pub bound_folder: Option<String>,
+    pub next_seq: u64, // Last seq handed out, so restored changes keep theirs
+    pub queue: Vec<FileChange>, // Unacknowledged changes, in seq order
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Store {
+    version: u32,
+    sessions: Vec<PersistedSession>,
+}
+
+/// `<app data>/RoBezy/sessions.json` ("~/Library/Application Support" on macOS, "%APPDATA%" on Windows).
+pub fn store_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("RoBezy").join(STORE_FILE))
+}
+
+/// The store file as last written. Shared with `PendingStore`s, which write outside the session lock.
+#[derive(Default)]
+struct Written {
+    json: Option<String>,
+    generation: u64, // Of the snapshot on disk, so an older one finishing late doesn't overwrite it
+}
+
+pub struct SessionStore {
+    path: Option<PathBuf>, // None when the platform has no app-data folder: nothing is kept
+    written: Arc<Mutex<Written>>,
+    generation: u64, // Last snapshot handed out by `prepare`
+}
+
+impl SessionStore {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { path, written: Arc::default(), generation: 0 }
+    }
+
+    /// The sessions saved by the previous run. A missing, broken or outdated store gives none.
+    pub fn load(&mut self) -> Vec<PersistedSession> {
+        let Some(path) = &self.path else { return Vec::new() };
+        let Ok(raw) = std::fs::read_to_string(path) else { return Vec::new() };
+        let store: Store = match serde_json::from_str(&raw) {
+            Ok(store) => store,
+            Err(e) => {
+                eprintln!("RoBezy: Ignoring invalid {}: {}", path.display(), e);
+                return Vec::new();
+            },
+        };
+        if store.version != STORE_VERSION {
+            eprintln!("RoBezy: Ignoring {} (version {}, expected {})", path.display(), store.version, STORE_VERSION);
+            return Vec::new();
+        }
+        self.written.lock().unwrap().json = Some(raw);
+        store.sessions
+    }
+
+    /// Serializes the sessions for `PendingStore::write`, which does the (fsynced) write and can
+    /// run once the session lock is released. None when there is nowhere to keep them.
+    pub fn prepare(&mut self, sessions: Vec<PersistedSession>) -> Result<Option<PendingStore>, String> {
+        let Some(path) = &self.path else { return Ok(None) };
+        let json = serde_json::to_string_pretty(&Store { version: STORE_VERSION, sessions })
+            .map_err(|e| format!("Failed to serialize {}: {}", STORE_FILE, e))?;
+        self.generation += 1;
+        Ok(Some(PendingStore { path: path.clone(), json, generation: self.generation, written: self.written.clone() }))
+    }
+}
+
+/// A serialized snapshot of the sessions, waiting to be written.
+pub struct PendingStore {
+    path: PathBuf,
+    json: String,
+    generation: u64,
+    written: Arc<Mutex<Written>>,
+}
+
+impl PendingStore {
+    /// Replaces the stored sessions. Skips the write when nothing changed since the last one, or
+    /// when a newer snapshot is already on disk.
+    pub fn write(self) -> Result<(), String> {
+        let mut written = self.written.lock().unwrap();
+        if self.generation < written.generation || written.json.as_deref() == Some(self.json.as_str()) {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        crate::robezy::fs::write_atomic(&self.path, &self.json).map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+        written.json = Some(self.json);
+        written.generation = self.generation;
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::robezy::fs::NativeFileManager;
+use crate::robezy::config::ReconnectPolicy;
+use crate::robezy::diff;
+use crate::robezy::model::{file_kind, FileKind};
+use crate::robezy::reconcile::{self, Action, FileState};
+use crate::robezy::session::{FileChange, ProjectFile};
+
+// Dry runs: what the outbound queue or a reconnect would do, with diffs. Only reads files and state.
+
+#[derive(Debug, Serialize)]
+pub struct PreviewChange {
+    pub action: &'static str, // "create", "update", "delete", "move" or "conflict"
+    pub target: &'static str, // "studio" or "disk": the side that would change
+    pub path: String, // Relative Path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub studio_path: Option<String>, // Instance path the plugin resolves (differs under a Rojo project)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<FileState>, // Reconnect only
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub held: bool, // Waiting on an open conflict
+    pub diff: String, // Unified diff of the file (empty for pure moves)
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PreviewSummary {
+    pub creates: usize,
+    pub updates: usize,
+    pub deletes: usize,
+    pub moves: usize,
+    pub conflicts: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Preview {
+    pub source: &'static str, // "queue" or "reconnect"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<ReconnectPolicy>,
+    pub summary: PreviewSummary,
+    pub changes: Vec<PreviewChange>,
+}
+
+impl Preview {
+    fn new(source: &'static str, policy: Option<ReconnectPolicy>, changes: Vec<PreviewChange>) -> Self {
+        let mut summary = PreviewSummary::default();
+        for change in &changes {
+            match change.action {
+                "create" => summary.creates += 1,
+                "update" => summary.updates += 1,
+                "delete" => summary.deletes += 1,
+                "move" => summary.moves += 1,
+                _ => summary.conflicts += 1,
+            }
+        }
+        Self { source, policy, summary, changes }
+    }
+}
+
+fn old_name(path: &str, exists: bool) -> String {
+    if exists { format!("a/{}", path) } else { "/dev/null".to_string() }
+}
+
+fn new_name(path: &str, exists: bool) -> String {
+    if exists { format!("b/{}", path) } else { "/dev/null".to_string() }
+}
+
+/// What Studio has for a queued path: the last content it confirmed, else its connect snapshot.
+fn studio_content(fm: &NativeFileManager, path: &str, snapshot: &[ProjectFile]) -> Option<String> {
+    fm.synced_content(Path::new(path)).or_else(|| {
+        snapshot.iter()
+            .find(|f| fm.to_disk_path(&f.path) == Path::new(path))
+            .map(|f| f.content.clone())
+    })
+}
+
+/// The changes waiting in the outbound queue, as Studio would apply them.
+/// `snapshot` is the session's connect upload (a fallback for Studio's current content).
+pub fn queue(fm: &NativeFileManager, queued: &[FileChange], held: &[String], snapshot: &[ProjectFile]) -> Preview {
+    let mut changes = Vec::new();
+    for change in queued {
+        let before = studio_content(fm, &change.path, snapshot);
+        let known = before.is_some() || fm.guid_for_path(Path::new(&change.path)).is_some();
+        let (action, diff) = match change.change_type.as_str() {
+            "write" => {
+                let after = change.content.as_deref().unwrap_or_default();
+                let diff = diff::unified(&old_name(&change.path, known), &new_name(&change.path, true), before.as_deref().unwrap_or_default(), after);
+                (if known { "update" } else { "create" }, diff)
+            },
+            "delete" => {
+                let diff = diff::unified(&old_name(&change.path, true), &new_name(&change.path, false), before.as_deref().unwrap_or_default(), "");
+                ("delete", diff)
+            },
+            "rename" | "move" => ("move", String::new()),
+            _ => continue,
+        };
+        changes.push(PreviewChange {
+            action,
+            target: "studio",
+            path: change.path.clone(),
+            studio_path: fm.to_studio_path(&change.path).filter(|p| *p != change.path),
+            old_path: change.old_path.clone(),
+            class_name: change.class_name.clone(),
+            state: None,
+            held: held.contains(&change.path),
+            diff,
+        });
+    }
+    Preview::new("queue", None, changes)
+}
+
+/// What connecting with `files` (Studio's upload) would do under `policy`. Paths are resolved
+/// without claiming them, so nothing in the index changes.
+pub fn reconnect(fm: &NativeFileManager, files: &[ProjectFile], policy: ReconnectPolicy) -> Preview {
+    let studio_files: Vec<(PathBuf, &ProjectFile)> = files.iter()
+        .map(|file| {
+            let is_script = matches!(file_kind(&file.path), Some(FileKind::Script(_)));
+            let relative = file.guid.as_deref()
+                .and_then(|guid| fm.resolve_path(guid, &file.instance_path(), is_script, file.class_name.as_deref()).ok())
+                .unwrap_or_else(|| fm.to_disk_path(&file.path));
+            (relative, file)
+        })
+        .collect();
+    let plan = reconcile::plan(fm, &studio_files, policy);
+
+    let mut changes = Vec::new();
+    for item in plan.items {
+        let studio = item.studio.as_deref().unwrap_or_default();
+        let disk = item.disk.as_deref().unwrap_or_default();
+        let (action, target, diff) = match item.action {
+            Action::PushToStudio => {
+                let exists = item.state != FileState::DiskOnly;
+                let diff = diff::unified(&old_name(&item.path, exists), &new_name(&item.path, true), studio, disk);
+                (if exists { "update" } else { "create" }, "studio", diff)
+            },
+            Action::WriteDisk => {
+                let exists = item.state != FileState::StudioOnly;
+                let diff = diff::unified(&old_name(&item.path, exists), &new_name(&item.path, true), disk, studio);
+                (if exists { "update" } else { "create" }, "disk", diff)
+            },
+            // Disk stays; the diff shows what Studio's copy would change
+            Action::Conflict => ("conflict", "disk", diff::unified(&old_name(&item.path, true), &new_name(&item.path, true), disk, studio)),
+            Action::None | Action::KeepDisk => continue,
+        };
+        changes.push(PreviewChange {
+            action,
+            target,
+            studio_path: fm.to_studio_path(&item.path).filter(|p| *p != item.path),
+            path: item.path,
+            old_path: None,
+            class_name: item.class_name,
+            state: Some(item.state),
+            held: false,
+            diff,
+        });
+    }
+    Preview::new("reconnect", Some(policy), changes)
+}
@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+use std::path::Path;
+use serde_json::Value;
+use crate::robezy::model::file_kind;
+
+/// Rojo project file. When a bound folder has one, its `$path` entries decide where
+/// instances live on disk instead of the default "<Service>/<Name>" layout.
+pub const PROJECT_FILE: &str = "default.project.json";
+
+// Services the plugin syncs. Without a project these are the top-level folders.
+const SYNCED_SERVICES: [&str; 9] = [
+    "Workspace", "ServerScriptService", "ReplicatedStorage", "ReplicatedFirst",
+    "StarterPlayer", "StarterPack", "StarterGui", "ServerStorage", "Lighting",
+];
+
+/// One `$path` entry: the instance at `instance` ("ReplicatedStorage", "Shared")
+/// is the folder or file at `path` ("src/shared").
+#[derive(Debug, Clone)]
+struct Mount {
+    instance: Vec<String>,
+    path: Vec<String>,
+    is_file: bool, // "$path": "src/main.server.lua"
+}
+
+/// Maps "Studio paths" (the service-first, slash-separated paths the plugin uses, e.g.
+/// "ReplicatedStorage/Shared/Util.lua") to paths inside the bound folder and back.
+/// Without a project file both sides are the same.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectMap {
+    mounts: Vec<Mount>,
+    services: HashSet<String>, // Top-level names in the tree
+}
+
+fn segments(path: &str) -> Vec<String> {
+    path.replace('\\', "/")
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// "Foo.server.lua" -> ("Foo", ".server.lua"), "Shared" -> ("Shared", "")
+fn split_leaf(leaf: &str) -> (&str, &str) {
+    match leaf.find('.') {
+        Some(idx) => leaf.split_at(idx),
+        None => (leaf, ""),
+    }
+}
+
+fn path_value(node: &Value) -> Option<&str> {
+    match node.get("$path")? {
+        Value::String(path) => Some(path),
+        other => other.get("optional").and_then(|p| p.as_str()), // { "optional": "src/x" }
+    }
+}
+
+impl ProjectMap {
+    /// Reads `default.project.json` from a bound folder. None if there is no (valid) project file.
+    pub fn load(root_dir: &Path) -> Option<Self> {
+        let path = root_dir.join(PROJECT_FILE);
+        let raw = std::fs::read_to_string(&path).ok()?;
+        let project: Value = match serde_json::from_str(&raw) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("RoBezy: Ignoring invalid {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        let tree = project.get("tree")?;
+        let mut map = ProjectMap::default();
+        map.collect(tree, &mut Vec::new());
+        if let Some(children) = tree.as_object() {
+            map.services = children.keys().filter(|k| !k.starts_with('$')).cloned().collect();
+        }
+        // Longest instance path first, so nested mounts win over their parents
+        map.mounts.sort_by_key(|b| std::cmp::Reverse(b.instance.len()));
+        Some(map)
+    }
+
+    fn collect(&mut self, node: &Value, instance: &mut Vec<String>) {
+        if let (false, Some(path)) = (instance.is_empty(), path_value(node)) {
+            self.mounts.push(Mount {
+                instance: instance.clone(),
+                path: segments(path),
+                is_file: file_kind(path).is_some(),
+            });
+        }
+        if let Some(children) = node.as_object() {
+            for (name, child) in children.iter().filter(|(k, _)| !k.starts_with('$')) {
+                instance.push(name.clone());
+                self.collect(child, instance);
+                instance.pop();
+            }
+        }
+    }
+
+    pub fn mount_count(&self) -> usize {
+        self.mounts.len()
+    }
+
+    /// "ReplicatedStorage/Shared/Util.lua" -> "src/shared/Util.lua".
+    /// Paths outside every `$path` keep the default layout.
+    pub fn to_disk(&self, studio_path: &str) -> String {
+        let parts = segments(studio_path);
+        let Some((leaf, dirs)) = parts.split_last() else {
+            return studio_path.to_string();
+        };
+        let (stem, suffix) = split_leaf(leaf);
+        // Instance this file belongs to ("init.lua" is its folder's instance)
+        let mut instance: Vec<&str> = dirs.iter().map(|s| s.as_str()).collect();
+        if stem != "init" {
+            instance.push(stem);
+        }
+
+        for mount in &self.mounts {
+            if instance.len() < mount.instance.len() || instance[..mount.instance.len()] != mount.instance[..] {
+                continue;
+            }
+            if mount.is_file {
+                if instance.len() != mount.instance.len() {
+                    continue; // Children of a file mount use the default layout
+                }
+                // The file itself, or a sibling with another suffix (its .meta.json)
+                let (file_stem, file_suffix) = split_leaf(mount.path.last().map(|s| s.as_str()).unwrap_or(""));
+                let mut out = mount.path[..mount.path.len().saturating_sub(1)].to_vec();
+                out.push(if suffix == file_suffix { format!("{}{}", file_stem, file_suffix) } else { format!("{}{}", file_stem, suffix) });
+                return out.join("/");
+            }
+            let mut out = mount.path.clone();
+            if instance.len() == mount.instance.len() && !suffix.is_empty() {
+                // A file for the mounted folder itself ("Shared.meta.json" -> "src/shared/init.meta.json")
+                out.push(format!("init{}", suffix));
+            } else {
+                out.extend(parts[mount.instance.len()..].iter().cloned());
+            }
+            return out.join("/");
+        }
+        parts.join("/")
+    }
+
+    /// "src/shared/Util.lua" -> "ReplicatedStorage/Shared/Util.lua".
+    /// None for files the project doesn't map (e.g. "README.md" next to "src/"), which are not synced.
+    pub fn to_studio(&self, disk_path: &str) -> Option<String> {
+        let parts = segments(disk_path);
+        let mut best: Option<(&Mount, String)> = None;
+        for mount in &self.mounts {
+            let mapped = if mount.is_file {
+                // The mounted file or a sibling sharing its stem (its .meta.json)
+                let (file_stem, _) = split_leaf(mount.path.last().map(|s| s.as_str()).unwrap_or(""));
+                let Some((leaf, dirs)) = parts.split_last() else { continue };
+                let (stem, suffix) = split_leaf(leaf);
+                if dirs != &mount.path[..mount.path.len().saturating_sub(1)] || stem != file_stem {
+                    continue;
+                }
+                let (name, parent) = mount.instance.split_last()?;
+                let mut out = parent.to_vec();
+                out.push(format!("{}{}", name, suffix));
+                out.join("/")
+            } else {
+                if parts.len() < mount.path.len() || parts[..mount.path.len()] != mount.path[..] {
+                    continue;
+                }
+                let rest = &parts[mount.path.len()..];
+                match rest {
+                    // "src/shared/init.lua" is the mounted instance itself
+                    [leaf] if split_leaf(leaf).0 == "init" => {
+                        let (name, parent) = mount.instance.split_last()?;
+                        let mut out = parent.to_vec();
+                        out.push(format!("{}{}", name, split_leaf(leaf).1));
+                        out.join("/")
+                    },
+                    _ => {
+                        let mut out = mount.instance.clone();
+                        out.extend(rest.iter().cloned());
+                        out.join("/")
+                    },
+                }
+            };
+            // Deepest disk path wins ("src/shared/ui" over "src/shared")
+            if best.as_ref().map(|(m, _)| mount.path.len() > m.path.len()).unwrap_or(true) {
+                best = Some((mount, mapped));
+            }
+        }
+        if let Some((_, mapped)) = best {
+            return Some(mapped);
+        }
+        // Unmapped: only the default "<Service>/..." layout for services the plugin syncs,
+        // and only where RoBezy itself would put that instance (not a stale copy under a mounted service)
+        let first = parts.first()?;
+        let joined = parts.join("/");
+        let is_service = self.services.contains(first) || SYNCED_SERVICES.contains(&first.as_str());
+        if is_service && self.to_disk(&joined) == joined {
+            Some(joined)
+        } else {
+            None
+        }
+    }
+}
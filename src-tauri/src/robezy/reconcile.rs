@@ -0,0 +1,386 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use crate::robezy::fs::{content_hash, NativeFileManager};
+use crate::robezy::conflict::{self, ConflictMap};
+use crate::robezy::config::ReconnectPolicy;
+use crate::robezy::model::{file_kind, FileKind, ScriptMeta};
+use crate::robezy::session::{FileChange, ProjectFile, SharedQueue};
+use crate::server_ws::InternalBroadcast;
+
+// On connect, every file Studio reports is compared with the bound folder and the content both
+// sides last agreed on (the index hash), instead of Studio blindly overwriting offline disk edits.
+
+/// How a file differs between Studio and disk.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileState {
+    Unchanged,
+    StudioNewer, // Only Studio moved away from the last sync
+    DiskNewer,   // Only disk moved away from the last sync
+    BothChanged, // Both did (or the file was never synced and they differ)
+    StudioOnly,  // Not on disk
+    DiskOnly,    // Studio didn't report it
+}
+
+/// What connecting does about it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    None,
+    WriteDisk,    // Studio's content replaces the file
+    PushToStudio, // The file is queued for Studio
+    Conflict,     // Disk is kept, Studio's copy goes to `<path>.conflict`
+    KeepDisk,     // Left on disk, nothing sent
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanItem {
+    pub path: String, // Relative Path
+    pub state: FileState,
+    pub action: Action,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guid: Option<String>,
+    #[serde(skip)]
+    pub class_name: Option<String>,
+    #[serde(skip)]
+    pub is_script: bool,
+    #[serde(skip)]
+    pub studio: Option<String>, // Studio's content
+    #[serde(skip)]
+    pub disk: Option<String>, // Disk content
+    #[serde(skip)]
+    pub meta: ScriptMeta,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Summary {
+    pub unchanged: usize,
+    pub studio_newer: usize,
+    pub disk_newer: usize,
+    pub both_changed: usize,
+    pub studio_only: usize,
+    pub disk_only: usize,
+    pub conflicts: usize, // Items that became conflicts under the policy
+}
+
+/// What a connect found and did. `items` lists every file that was not unchanged.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconcilePlan {
+    pub policy: ReconnectPolicy,
+    pub summary: Summary,
+    pub items: Vec<PlanItem>,
+    #[serde(skip)]
+    pub unchanged: Vec<PlanItem>,
+}
+
+fn classify(studio: &str, disk: Option<&str>, base: Option<&str>) -> FileState {
+    let Some(disk) = disk else { return FileState::StudioOnly };
+    let (studio_hash, disk_hash) = (content_hash(studio), content_hash(disk));
+    if studio_hash == disk_hash {
+        return FileState::Unchanged;
+    }
+    match base {
+        Some(base) if disk_hash == base => FileState::StudioNewer,
+        Some(base) if studio_hash == base => FileState::DiskNewer,
+        _ => FileState::BothChanged,
+    }
+}
+
+fn action_for(policy: ReconnectPolicy, state: FileState) -> Action {
+    match (state, policy) {
+        (FileState::Unchanged, _) => Action::None,
+        (FileState::StudioOnly, _) => Action::WriteDisk,
+        (FileState::StudioNewer, ReconnectPolicy::Disk) => Action::PushToStudio,
+        (FileState::StudioNewer, _) => Action::WriteDisk,
+        (FileState::DiskNewer, ReconnectPolicy::Studio) => Action::WriteDisk,
+        (FileState::DiskNewer, _) => Action::PushToStudio,
+        (FileState::BothChanged, ReconnectPolicy::Studio) => Action::WriteDisk,
+        (FileState::BothChanged, ReconnectPolicy::Disk) => Action::PushToStudio,
+        (FileState::BothChanged, ReconnectPolicy::Auto) => Action::Conflict,
+        (FileState::DiskOnly, ReconnectPolicy::Studio) => Action::KeepDisk,
+        (FileState::DiskOnly, _) => Action::PushToStudio,
+    }
+}
+
+/// Compares Studio's files (already resolved to their relative paths) with the bound folder.
+/// Reads only; nothing is written or queued.
+pub fn plan(fm: &NativeFileManager, studio_files: &[(PathBuf, &ProjectFile)], policy: ReconnectPolicy) -> ReconcilePlan {
+    let mut summary = Summary::default();
+    let mut items = Vec::new();
+    let mut unchanged = Vec::new();
+    let mut reported = HashSet::new();
+
+    for (relative, file) in studio_files {
+        let path = relative.to_string_lossy().replace('\\', "/");
+        if fm.is_excluded(&path, false) {
+            continue; // Studio has it, but the folder opted out of syncing it
+        }
+        if let Err(e) = fm.full_path(relative) {
+            eprintln!("RoBezy: Skipping {}: {}", path, e);
+            continue;
+        }
+        let disk = match fm.read_text(relative) {
+            Ok(disk) => Some(disk),
+            Err(_) if !fm.root_dir.join(relative).exists() => None,
+            Err(e) => {
+                eprintln!("RoBezy: Skipping {}", e); // Unreadable under the encoding policy: leave it alone
+                continue;
+            },
+        };
+        reported.insert(path.clone());
+        let studio = fm.normalize_text(&file.content);
+        let base = fm.synced_hash(relative);
+        let state = classify(&studio, disk.as_deref(), base.as_deref());
+        let item = PlanItem {
+            path,
+            state,
+            action: action_for(policy, state),
+            guid: file.guid.clone(),
+            class_name: file.class_name.clone(),
+            is_script: matches!(file_kind(&file.path), Some(FileKind::Script(_))),
+            studio: Some(studio),
+            disk,
+            meta: file.meta.clone(),
+        };
+        if state == FileState::Unchanged {
+            unchanged.push(item);
+        } else {
+            items.push(item);
+        }
+    }
+
+    // Scripts and text files Studio didn't report. Model/meta files are disk-only by nature.
+    for relative in fm.synced_files() {
+        let path = relative.to_string_lossy().replace('\\', "/");
+        let (class_name, is_script) = match file_kind(&path) {
+            Some(FileKind::Script(class)) => (class, true),
+            Some(FileKind::Text(class)) => (class, false),
+            _ => continue,
+        };
+        if reported.contains(&path) || fm.to_studio_path(&path).is_none() {
+            continue;
+        }
+        let disk = match fm.read_text(&relative) {
+            Ok(disk) => disk,
+            Err(e) => {
+                eprintln!("RoBezy: Skipping {}", e);
+                continue;
+            },
+        };
+        // Synced before and untouched since: Studio deleted it while we were apart. Don't resurrect it.
+        let deleted_in_studio = fm.synced_hash(&relative).as_deref() == Some(content_hash(&disk).as_str());
+        let action = match action_for(policy, FileState::DiskOnly) {
+            Action::PushToStudio if deleted_in_studio && policy == ReconnectPolicy::Auto => Action::KeepDisk,
+            action => action,
+        };
+        items.push(PlanItem {
+            path,
+            state: FileState::DiskOnly,
+            action,
+            guid: fm.guid_for_path(&relative),
+            class_name: Some(class_name.to_string()),
+            is_script,
+            studio: None,
+            disk: Some(disk),
+            meta: ScriptMeta::default(),
+        });
+    }
+
+    summary.unchanged = unchanged.len();
+    for item in &items {
+        match item.state {
+            FileState::Unchanged => {},
+            FileState::StudioNewer => summary.studio_newer += 1,
+            FileState::DiskNewer => summary.disk_newer += 1,
+            FileState::BothChanged => summary.both_changed += 1,
+            FileState::StudioOnly => summary.studio_only += 1,
+            FileState::DiskOnly => summary.disk_only += 1,
+        }
+        if item.action == Action::Conflict {
+            summary.conflicts += 1;
+        }
+    }
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    ReconcilePlan { policy, summary, items, unchanged }
+}
+
+/// Carries out a plan: writes Studio's side to disk, queues disk's side for Studio and opens conflicts.
+/// Conflicts are broadcast like any other; the caller reports the plan itself.
+pub fn apply(fm: &NativeFileManager, queue: &SharedQueue, conflicts: &ConflictMap, events: &broadcast::Sender<InternalBroadcast>, session_id: &str, plan: &ReconcilePlan) {
+    for item in plan.unchanged.iter().chain(plan.items.iter()) {
+        let relative = Path::new(&item.path);
+        match item.action {
+            Action::None | Action::WriteDisk => {
+                let content = item.studio.as_deref().unwrap_or_default();
+                if item.action == Action::WriteDisk {
+                    let full_path = match fm.full_path(relative) {
+                        Ok(full_path) => full_path,
+                        Err(e) => {
+                            eprintln!("RoBezy: Failed to write initial file {}: {}", item.path, e);
+                            continue;
+                        },
+                    };
+                    if let Some(parent) = full_path.parent() {
+                        let _ = std::fs::create_dir_all(parent);
+                    }
+                    fm.suppress(relative);
+                    if let Err(e) = fm.write_text(&full_path, content) {
+                        eprintln!("RoBezy: Failed to write initial file {}: {}", item.path, e);
+                        continue;
+                    }
+                }
+                fm.mark_synced(relative, content);
+                if let Err(e) = fm.write_sidecar(relative, &item.meta) {
+                    eprintln!("RoBezy: Failed to write metadata for {}: {}", item.path, e);
+                }
+            },
+            Action::PushToStudio => push_to_studio(queue, item),
+            Action::Conflict => {
+                let copy = conflict::conflict_path(&item.path);
+                fm.suppress(Path::new(&copy));
+                let written = fm.full_path(Path::new(&copy))
+                    .and_then(|full| fm.write_text(&full, item.studio.as_deref().unwrap_or_default()).map_err(|e| e.to_string()));
+                if let Err(e) = written {
+                    eprintln!("RoBezy Conflict Error: {}", e);
+                    continue;
+                }
+                if let Some(c) = conflict::open(conflicts, &item.path, item.guid.clone(), item.class_name.clone()) {
+                    println!("RoBezy: Conflict on {} (Studio copy in {})", c.path, c.conflict_path);
+                    let _ = events.send(InternalBroadcast::Conflict {
+                        session_id: session_id.to_string(),
+                        path: c.path,
+                        conflict_path: c.conflict_path,
+                        guid: c.guid,
+                    });
+                }
+            },
+            Action::KeepDisk => {},
+        }
+    }
+}
+
+/// Queues the item's disk content for Studio, replacing anything still queued for the path.
+fn push_to_studio(queue: &SharedQueue, item: &PlanItem) {
+    let mut q = queue.lock().unwrap();
+    q.retain(|c| c.path != item.path);
+    q.push(FileChange {
+        change_type: "write".to_string(),
+        path: item.path.clone(),
+        content: item.disk.clone(),
+        is_script: item.is_script,
+        guid: item.guid.clone(),
+        class_name: item.class_name.clone(),
+        ..Default::default()
+    });
+}
+
+/// The disk half of a reconcile, for a session restored after an app restart: scripts and text
+/// files edited or added while the app was closed (their content no longer matches the index hash)
+/// are queued for Studio, as the watcher would have done. Returns how many were queued.
+pub fn queue_disk_changes(fm: &NativeFileManager, queue: &SharedQueue) -> usize {
+    let mut queued = 0;
+    for relative in fm.synced_files() {
+        let path = relative.to_string_lossy().replace('\\', "/");
+        let (class_name, is_script) = match file_kind(&path) {
+            Some(FileKind::Script(class)) => (class, true),
+            Some(FileKind::Text(class)) => (class, false),
+            _ => continue,
+        };
+        if fm.to_studio_path(&path).is_none() {
+            continue;
+        }
+        let disk = match fm.read_text(&relative) {
+            Ok(disk) => disk,
+            Err(e) => {
+                eprintln!("RoBezy: Skipping {}", e);
+                continue;
+            },
+        };
+        let base = fm.synced_hash(&relative);
+        if base.as_deref() == Some(content_hash(&disk).as_str()) {
+            continue;
+        }
+        push_to_studio(queue, &PlanItem {
+            path,
+            state: if base.is_some() { FileState::DiskNewer } else { FileState::DiskOnly },
+            action: Action::PushToStudio,
+            guid: fm.guid_for_path(&relative),
+            class_name: Some(class_name.to_string()),
+            is_script,
+            studio: None,
+            disk: Some(disk),
+            meta: ScriptMeta::default(),
+        });
+        queued += 1;
+    }
+    queued
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::robezy::session::OutboundQueue;
+
+    /// A bound folder in the temp dir, removed when dropped
+    struct Folder(PathBuf);
+
+    impl Folder {
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("robezy-reconcile-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&root).unwrap();
+            Self(root)
+        }
+
+        fn file_manager(&self) -> NativeFileManager {
+            NativeFileManager::new(self.0.clone(), Default::default())
+        }
+    }
+
+    impl Drop for Folder {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn queued(fm: &NativeFileManager) -> usize {
+        let queue: SharedQueue = Arc::new(Mutex::new(OutboundQueue::default()));
+        let count = queue_disk_changes(fm, &queue);
+        assert_eq!(queue.lock().unwrap().len(), count);
+        count
+    }
+
+    #[test]
+    fn disk_created_file_is_not_pushed_again_after_restart() {
+        let folder = Folder::new();
+        std::fs::write(folder.0.join("Foo.lua"), "print(1)\n").unwrap();
+
+        // Never synced: pushed as disk-only
+        let fm = folder.file_manager();
+        assert_eq!(queued(&fm), 1);
+
+        // Studio acked it without a GUID owning the path yet
+        fm.mark_synced(Path::new("Foo.lua"), "print(1)\n");
+        fm.save_index();
+
+        let restored = folder.file_manager();
+        assert_eq!(queued(&restored), 0);
+
+        std::fs::write(folder.0.join("Foo.lua"), "print(2)\n").unwrap();
+        assert_eq!(queued(&restored), 1);
+    }
+
+    #[test]
+    fn unowned_hash_goes_to_the_guid_that_claims_the_path() {
+        let folder = Folder::new();
+        std::fs::write(folder.0.join("Foo.lua"), "print(1)\n").unwrap();
+        let fm = folder.file_manager();
+        fm.mark_synced(Path::new("Foo.lua"), "print(1)\n");
+
+        assert_eq!(fm.assign_path("guid-1", "Foo", true, Some("ModuleScript")), Some(PathBuf::from("Foo.lua")));
+        assert_eq!(fm.synced_hash(Path::new("Foo.lua")), Some(content_hash("print(1)\n")));
+        assert_eq!(queued(&fm), 0);
+    }
+}
@@ -2,14 +2,17 @@ use std::sync::{Arc, Mutex};
 use warp::Filter;
 use serde::Deserialize; 
 use std::net::SocketAddr;
-use crate::robezy::session::{SessionManager, SessionIdentity, FileChange};
+use crate::robezy::session::{SessionManager, SessionIdentity, FileChange, STATE_SAVE_DELAY};
+use crate::robezy::fs::{write_atomic, NativeFileManager};
+use crate::robezy::conflict::{self, ConflictMap, Resolution};
+use crate::robezy::config::{ReconnectPolicy, ScriptExtension};
+use crate::robezy::preview;
+use crate::robezy::upload::{Chunk, Manifest, MAX_CHUNK_BODY, MAX_MANIFEST_BODY};
+use crate::safe_path;
+use crate::server_ws::{InternalBroadcast, MAX_LONG_POLL_SECS};
+use tokio::sync::{broadcast, Notify};
 
 // Request Structs must be module-level for safety
-#[derive(Deserialize)]
-struct UploadRequest {
-    session_id: String,
-    files: Vec<crate::robezy::session::ProjectFile>,
-}
 
 #[derive(Deserialize)]
 struct ConnectRequest {
@@ -19,6 +22,8 @@ struct ConnectRequest {
     project_id: Option<String>,
     #[serde(default)]
     files: Vec<crate::robezy::session::ProjectFile>,
+    #[serde(default)]
+    upload_id: Option<String>, // A complete chunked upload holding (the rest of) the files
 }
 
 #[derive(Deserialize)]
@@ -47,16 +52,98 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
         }
     });
 
+    // Queue pushes and acks ask for a save; a burst of them is written once
+    let store_mgr = session_manager.clone();
+    let state_changed = session_manager.lock().unwrap().state_changed.clone();
+    tokio::spawn(async move {
+        loop {
+            state_changed.notified().await;
+            tokio::time::sleep(STATE_SAVE_DELAY).await;
+            let Some(pending) = store_mgr.lock().unwrap().state_snapshot() else { continue };
+            match tokio::task::spawn_blocking(move || pending.write()).await {
+                Ok(Err(e)) => eprintln!("RoBezy: {}", e),
+                Err(e) => eprintln!("RoBezy: Session save failed: {}", e),
+                Ok(Ok(())) => {},
+            }
+        }
+    });
+
+    // Workspace snapshots (POST :3030/roblox/workspace) tell the sourcemap the classes of folders and services
+    let snapshot_mgr = session_manager.clone();
+    let mut snapshots = session_manager.lock().unwrap().events.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match snapshots.recv().await {
+                Ok(InternalBroadcast::WorkspaceEvent(body)) => {
+                    let (Some(session_id), Some(items)) = (
+                        body.get("session_id").and_then(|s| s.as_str()),
+                        body.get("items").and_then(|i| i.as_array()),
+                    ) else {
+                        continue;
+                    };
+                    let fm = snapshot_mgr.lock().unwrap().get_file_manager(session_id).cloned();
+                    if let Some(fm) = fm {
+                        fm.apply_snapshot(items);
+                    }
+                },
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {},
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
     let session_manager = warp::any().map(move || session_manager.clone());
     
     // POST /robezy/upload
+    // Starts a chunked upload from its manifest. Replies with the upload id and the chunks to send.
     let upload_route = warp::path!("robezy" / "upload")
         .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_MANIFEST_BODY))
         .and(warp::body::json())
         .and(session_manager.clone())
-        .map(|req: UploadRequest, manager: Arc<Mutex<SessionManager>>| {
-             manager.lock().unwrap().stage_files(req.session_id, req.files);
-             warp::reply::json(&"uploaded")
+        .map(|manifest: Manifest, manager: Arc<Mutex<SessionManager>>| {
+            let (session_id, chunk_count, file_count) = (manifest.session_id.clone(), manifest.chunk_count, manifest.file_count);
+            match manager.lock().unwrap().uploads.start(manifest) {
+                Ok(status) => {
+                    println!("RoBezy Upload: {} started for {} ({} files in {} chunks)", status.upload_id, session_id, file_count, chunk_count);
+                    warp::reply::json(&status)
+                },
+                Err(e) => warp::reply::json(&format!("error: {}", e)),
+            }
+        });
+
+    // POST /robezy/upload/:id/chunk
+    let upload_chunk_route = warp::path!("robezy" / "upload" / String / "chunk")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_CHUNK_BODY))
+        .and(warp::body::json())
+        .and(session_manager.clone())
+        .map(|upload_id: String, chunk: Chunk, manager: Arc<Mutex<SessionManager>>| {
+            let seq = chunk.seq;
+            match manager.lock().unwrap().uploads.add_chunk(&upload_id, chunk) {
+                Ok(status) => {
+                    if status.complete {
+                        println!("RoBezy Upload: {} complete ({} chunks)", upload_id, status.chunk_count);
+                    }
+                    warp::reply::json(&status)
+                },
+                Err(e) => {
+                    eprintln!("RoBezy Upload: Rejected chunk {} of {}: {}", seq, upload_id, e);
+                    warp::reply::json(&format!("error: {}", e))
+                },
+            }
+        });
+
+    // GET /robezy/upload/:id
+    // Which chunks arrived, so an interrupted upload can resend only the missing ones.
+    let upload_status_route = warp::path!("robezy" / "upload" / String)
+        .and(warp::get())
+        .and(session_manager.clone())
+        .map(|upload_id: String, manager: Arc<Mutex<SessionManager>>| {
+            match manager.lock().unwrap().uploads.status(&upload_id) {
+                Some(status) => warp::reply::json(&status),
+                None => warp::reply::json(&format!("error: unknown upload {} (expired?)", upload_id)),
+            }
         });
 
     // POST /robezy/connect
@@ -64,7 +151,7 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
         .and(warp::post())
         .and(warp::body::json())
         .and(session_manager.clone())
-        .map(|req: ConnectRequest, manager: Arc<Mutex<SessionManager>>| {
+        .then(|req: ConnectRequest, manager: Arc<Mutex<SessionManager>>| async move {
             let identity = SessionIdentity {
                 place_id: req.place_id,
                 place_name: req.place_name,
@@ -73,12 +160,33 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
             };
             
             println!("RoBezy HTTP: Connecting {} ({})", identity.place_name, identity.session_id);
-            let final_id = manager.lock().unwrap().register_session(identity.clone(), req.files);
+            let (final_id, reconcile, pending) = {
+                let mut mgr = manager.lock().unwrap();
+                let mut files = req.files;
+                if let Some(upload_id) = &req.upload_id {
+                    match mgr.uploads.take(upload_id, &identity.session_id) {
+                        Ok(mut uploaded) => {
+                            println!("RoBezy: Merging {} uploaded files into connection", uploaded.len());
+                            uploaded.append(&mut files);
+                            files = uploaded;
+                        },
+                        Err(e) => return warp::reply::json(&format!("error: {}", e)),
+                    }
+                }
+                let (final_id, pending) = mgr.register_session(identity.clone(), files);
+                let reconcile = mgr.get_session(&identity.session_id).and_then(|s| s.last_reconcile.clone());
+                (final_id, reconcile, pending)
+            };
+            // Disk writes happen without the manager lock, but before the plugin hears it is connected
+            if let Some(pending) = pending {
+                pending.apply().await;
+            }
             
             warp::reply::json(&serde_json::json!({
                 "status": "connected",
                 "session_id": identity.session_id,
-                "project_id": final_id
+                "project_id": final_id,
+                "reconcile": reconcile
             }))
         });
 
@@ -143,68 +251,87 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
         .and(session_manager.clone())
         .map(|req: SyncRequest, manager: Arc<Mutex<SessionManager>>| {
             // Retrieve Session Metadata (Clone Arc maps) to avoid holding lock during async write
-            let (maybe_fm, ignore_map) = {
+            let (maybe_fm, maybe_conflicts, events) = {
                 let mgr = manager.lock().unwrap();
-                let fm = mgr.get_file_manager(&req.session_id).cloned();
-                let ignores = mgr.get_session(&req.session_id).map(|s| s.ignore_paths.clone());
-                (fm, ignores)
+                (
+                    mgr.get_file_manager(&req.session_id).cloned(),
+                    mgr.get_session(&req.session_id).map(|s| s.conflicts.clone()),
+                    mgr.events.clone(),
+                )
             };
 
-            if let Some(fm) = maybe_fm {
+            if let (Some(fm), Some(conflicts)) = (maybe_fm, maybe_conflicts) {
+                let session_id = req.session_id.clone();
                 // Spawn async task with the CLONED fm (which shares internal state via Arc)
+                // ANTI-LOOP: NativeFileManager registers every path it touches in the watcher's ignore map.
                 tokio::spawn(async move {
                     for change in req.changes {
-                        if change.change_type == "write" {
-                            if let Some(content) = change.content {
-                                let guid_to_use = change.guid.clone().unwrap_or_default();
-                                
-                                // ANTI-LOOP: Register this path as ignored for 2 seconds
-                                if let Some(ref map) = ignore_map {
-                                    // change.path comes from Studio (e.g. Workspace.Part)
-                                    // Our watcher uses mapped paths. Ideally we use the FS path. 
-                                    // But since we don't know the exact FS path easily here without resolving,
-                                    // Let's rely on the fact that fs.rs writes to a path derived from this.
-                                    // Wait, fs.rs maps "Workspace.Part" -> "Workspace/Part.server.lua" (or similar).
-                                    // The Watcher sees "Workspace/Part.server.lua".
-                                    // We need to register "Workspace/Part.server.lua" in the ignore map.
-                                    // Since we don't have the fs mapper logic exposed here easily...
-                                    // Actually, we can just use the 'path' from the request IF we normalize it? 
-                                    // No, watcher provides "Workspace/Part.server.lua".
-                                    // We need to predict the FS path.
-                                    // Let's simplisticly assume standard mapping for now or fix fs.rs to return it.
-                                    // Or: Modify NativeFileManager to return the written path?
-                                    // Let's chance it: The FS event will happen.
-                                    // Just ignoring "Workspace/Part.server.lua" is hard if we don't know extension.
-                                    // Alternative: Ignore *any* event that resolves to this Roblox Path?
-                                    
-                                    // BETTER: During write_file_guid, the NativeFileManager knows the path. 
-                                    // But modifying that trait is deeply invasive.
-                                    
-                                    // BEST EFFORT: We know the extension based on ClassName.
-                                    let ext = match change.class_name.as_deref().unwrap_or("ModuleScript") {
-                                        "Script" => "server.lua",
-                                        "LocalScript" => "client.lua",
-                                        _ => "lua"
-                                    };
-                                    // Convert dots to slashes? No, path is "Workspace.Part".
-                                    // NativeFileManager converts dots to slashes.
-                                    let rel_path = change.path.replace(".", "/") + "." + ext;
-                                    
-                                    // Lock & Add
-                                    let mut ignores = map.lock().unwrap();
-                                    ignores.insert(rel_path.clone(), std::time::Instant::now() + std::time::Duration::from_secs(2));
-                                    // Also try with just .lua just in case?
-                                    // ignores.insert(change.path.replace(".", "/") + ".lua", ...);
+                        let guid_to_use = change.guid.clone().unwrap_or_default();
+                        match change.change_type.as_str() {
+                            "write" => {
+                                if let Some(content) = change.content.clone() {
+                                    // Three-way check: if disk also changed since the last sync, keep disk and park Studio's copy
+                                    if let Ok(relative) = fm.resolve_path(&guid_to_use, &change.path, change.is_script, change.class_name.as_deref()) {
+                                        let open = conflicts.lock().unwrap().contains_key(&relative.to_string_lossy().replace("\\", "/"));
+                                        if open || fm.diverged(&relative, &content) {
+                                            park_studio_version(&fm, &conflicts, &events, &session_id, &relative, &change, &content).await;
+                                            continue;
+                                        }
+                                    }
+                                    match fm.write_file_guid(&guid_to_use, &change.path, change.is_script, change.class_name.as_deref(), content, &change.meta).await {
+                                        Ok(outcome) => {
+                                            println!("RoBezy: Synced {} ({})", change.path, guid_to_use);
+                                            if let Some(class_change) = outcome.class_change {
+                                                println!("RoBezy: {} changed class, renamed {} -> {}", change.path, class_change.old_path.display(), outcome.path.display());
+                                                let _ = events.send(InternalBroadcast::ClassChanged {
+                                                    session_id: session_id.clone(),
+                                                    guid: guid_to_use.clone(),
+                                                    old_path: class_change.old_path.to_string_lossy().replace("\\", "/"),
+                                                    new_path: outcome.path.to_string_lossy().replace("\\", "/"),
+                                                    old_class: class_change.old_class,
+                                                    new_class: class_change.new_class,
+                                                });
+                                            }
+                                        },
+                                        Err(e) => eprintln!("RoBezy Sync Error: {}", e),
+                                    }
+                                }
+                            },
+                            "delete" => {
+                                match fm.delete_file_guid(&guid_to_use, &change.path, change.is_script, change.class_name.as_deref()).await {
+                                    Ok(rel) => println!("RoBezy: Deleted {} ({})", rel.display(), guid_to_use),
+                                    Err(e) => eprintln!("RoBezy Sync Error: {}", e),
+                                }
+                            },
+                            "rename" | "move" => {
+                                match fm.move_file_guid(&guid_to_use, change.old_path.as_deref(), &change.path, change.is_script, change.class_name.as_deref()).await {
+                                    Ok(rel) => println!("RoBezy: Moved {} -> {} ({})", change.old_path.as_deref().unwrap_or("?"), rel.display(), guid_to_use),
+                                    Err(e) => {
+                                        // Nothing to move (never written yet?) -> write it fresh at the new path
+                                        eprintln!("RoBezy Sync: Move failed ({}), writing instead", e);
+                                        if let Some(content) = change.content {
+                                            if let Err(e) = fm.write_file_guid(&guid_to_use, &change.path, change.is_script, change.class_name.as_deref(), content, &change.meta).await {
+                                                eprintln!("RoBezy Sync Error: {}", e);
+                                            }
+                                        }
+                                    }
                                 }
-                                
-                                if let Err(e) = fm.write_file_guid(&guid_to_use, &change.path, change.is_script, change.class_name.as_deref(), content).await {
-                                    eprintln!("RoBezy Sync Error: {}", e);
-                                } else {
-                                    println!("RoBezy: Synced {} ({})", change.path, guid_to_use);
+                            },
+                            "conflict" => {
+                                // The plugin got a disk change for a script with unsynced Studio edits and did not apply it
+                                if let Some(content) = change.content.as_deref() {
+                                    match fm.resolve_path(&guid_to_use, &change.path, change.is_script, change.class_name.as_deref()) {
+                                        Ok(relative) => park_studio_version(&fm, &conflicts, &events, &session_id, &relative, &change, content).await,
+                                        Err(e) => eprintln!("RoBezy Sync Error: {}", e),
+                                    }
                                 }
-                            }
+                            },
+                            other => eprintln!("RoBezy Sync: Unknown change type '{}'", other),
                         }
                     }
+                    // One index write for the whole batch
+                    fm.persist_index().await;
+                    fm.flush_sourcemap();
                 });
                 
                 warp::reply::json(&"syncing")
@@ -213,26 +340,157 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
             }
         });
 
-    // GET /robezy/poll_changes?session_id=...
+    // GET /robezy/poll_changes?session_id=...&after=<seq>&wait=<secs>
     #[derive(Deserialize)]
     struct PollQuery {
         session_id: String,
+        after: Option<u64>, // Highest seq the plugin has seen. Without it, delivery counts as acknowledgement (legacy)
+        wait: Option<u64>,  // Long-poll seconds (capped at MAX_LONG_POLL_SECS)
     }
 
     let poll_route = warp::path!("robezy" / "poll_changes")
+        .and(warp::get())
+        .and(warp::query::<PollQuery>())
+        .and(session_manager.clone())
+        .then(|query: PollQuery, manager: Arc<Mutex<SessionManager>>| async move {
+            // ?wait=<secs>: hold the request until a change is queued (long-poll)
+            let wait = std::time::Duration::from_secs(query.wait.unwrap_or(0).min(MAX_LONG_POLL_SECS));
+            let deadline = tokio::time::Instant::now() + wait;
+            loop {
+                let (changes, ready, applied) = match take_changes(&manager, &query.session_id, query.after) {
+                    Some(found) => found,
+                    // If session not found, return empty array to avoid breaking client
+                    None => return warp::reply::json(&Vec::<FileChange>::new()),
+                };
+                if let Some(fm) = applied {
+                    fm.persist_index().await;
+                }
+                let now = tokio::time::Instant::now();
+                if !changes.is_empty() || now >= deadline {
+                    return warp::reply::json(&changes);
+                }
+                let _ = tokio::time::timeout(deadline - now, ready.notified()).await;
+            }
+        });
+
+    // POST /robezy/ack
+    // The plugin confirms which changes it applied; everything else is redelivered until it expires.
+    #[derive(Deserialize)]
+    struct AckRequest {
+        session_id: String,
+        seqs: Vec<u64>,
+    }
+
+    let ack_route = warp::path!("robezy" / "ack")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(session_manager.clone())
+        .then(|req: AckRequest, manager: Arc<Mutex<SessionManager>>| async move {
+            let (acked, pending, applied) = {
+                let mgr = manager.lock().unwrap();
+                let Some(session) = mgr.get_session(&req.session_id) else {
+                    return warp::reply::json(&"error: session not found");
+                };
+                let (acked, pending) = {
+                    let mut queue = session.outbound_queue.lock().unwrap();
+                    let acked = queue.ack(&req.seqs);
+                    (acked, queue.len())
+                };
+                let applied = mgr.get_file_manager(&req.session_id).filter(|fm| mark_applied(fm, &acked)).cloned();
+                (acked.len(), pending, applied)
+            };
+            // The new sync base is saved once the locks are released
+            if let Some(fm) = applied {
+                fm.persist_index().await;
+            }
+            warp::reply::json(&serde_json::json!({ "acked": acked, "pending": pending }))
+        });
+
+    // GET /robezy/conflicts?session_id=...
+    let conflicts_route = warp::path!("robezy" / "conflicts")
         .and(warp::get())
         .and(warp::query::<PollQuery>())
         .and(session_manager.clone())
         .map(|query: PollQuery, manager: Arc<Mutex<SessionManager>>| {
             let mgr = manager.lock().unwrap();
-            if let Some(session) = mgr.get_session(&query.session_id) {
-                let mut queue = session.outbound_queue.lock().unwrap();
-                let changes: Vec<FileChange> = queue.drain(..).collect();
-                warp::reply::json(&changes)
-            } else {
-                // If session not found, return empty array to avoid breaking client
-                let empty: Vec<FileChange> = Vec::new();
-                warp::reply::json(&empty)
+            let open: Vec<conflict::Conflict> = match mgr.get_session(&query.session_id) {
+                Some(session) => session.conflicts.lock().unwrap().values().cloned().collect(),
+                None => Vec::new(),
+            };
+            warp::reply::json(&open)
+        });
+
+    // POST /robezy/resolve_conflict
+    #[derive(Deserialize)]
+    struct ResolveConflictRequest {
+        session_id: String,
+        path: String,       // Relative path of the conflicted file
+        resolution: String, // "studio" | "disk" | "merged"
+        content: Option<String>, // Required for "merged"
+    }
+
+    let resolve_conflict_route = warp::path!("robezy" / "resolve_conflict")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(session_manager.clone())
+        .then(|req: ResolveConflictRequest, manager: Arc<Mutex<SessionManager>>| async move {
+            let resolution = match Resolution::parse(&req.resolution, req.content) {
+                Ok(r) => r,
+                Err(e) => return warp::reply::json(&format!("error: {}", e)),
+            };
+            let found = {
+                let mgr = manager.lock().unwrap();
+                match (mgr.get_file_manager(&req.session_id).cloned(), mgr.get_session(&req.session_id)) {
+                    (Some(fm), Some(session)) => Some((fm, session.outbound_queue.clone(), session.conflicts.clone(), mgr.events.clone())),
+                    _ => None,
+                }
+            };
+            let (fm, queue, conflicts, events) = match found {
+                Some(found) => found,
+                None => return warp::reply::json(&"error: session/bind not found"),
+            };
+            let path = req.path.replace("\\", "/");
+            match conflict::resolve(&fm, &queue, &conflicts, &path, &resolution).await {
+                Ok(_) => {
+                    println!("RoBezy: Resolved conflict on {} ({})", path, resolution.as_str());
+                    let _ = events.send(InternalBroadcast::ConflictResolved {
+                        session_id: req.session_id,
+                        path,
+                        resolution: resolution.as_str().to_string(),
+                    });
+                    warp::reply::json(&"resolved")
+                },
+                Err(e) => warp::reply::json(&format!("error: {}", e)),
+            }
+        });
+
+    // POST /robezy/migrate_extension
+    // Renames every script in the bound folder to .lua or .luau and makes it the project's choice.
+    #[derive(Deserialize)]
+    struct MigrateExtensionRequest {
+        session_id: String,
+        extension: String, // "lua" | "luau"
+    }
+
+    let migrate_extension_route = warp::path!("robezy" / "migrate_extension")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(session_manager.clone())
+        .map(|req: MigrateExtensionRequest, manager: Arc<Mutex<SessionManager>>| {
+            let target = match ScriptExtension::parse(&req.extension) {
+                Ok(t) => t,
+                Err(e) => return warp::reply::json(&format!("error: {}", e)),
+            };
+            let fm = match manager.lock().unwrap().get_file_manager(&req.session_id).cloned() {
+                Some(fm) => fm,
+                None => return warp::reply::json(&"error: session/bind not found"),
+            };
+            match fm.migrate_script_extension(target) {
+                Ok(renamed) => {
+                    println!("RoBezy: Migrated {} scripts to .{}", renamed, target.as_str());
+                    warp::reply::json(&serde_json::json!({ "renamed": renamed, "extension": target.as_str() }))
+                },
+                Err(e) => warp::reply::json(&format!("error: {}", e)),
             }
         });
 
@@ -250,32 +508,34 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
         .and(warp::post())
         .and(warp::body::json())
         .and(session_manager.clone())
-        .map(|req: ProxyWriteRequest, manager: Arc<Mutex<SessionManager>>| {
-            let mgr = manager.lock().unwrap();
-            if let Some(session) = mgr.get_session(&req.session_id) {
-                if let Some(bound_folder) = &session.bound_folder {
-                   let full_path = std::path::Path::new(bound_folder).join(&req.path);
-                   
-                   // Security check: simple path traversal prevention
-                   // (Allowing only if it starts with bound_folder)
-                   if full_path.starts_with(bound_folder) {
-                        // Ensure parent dir exists
-                        if let Some(parent) = full_path.parent() {
-                            let _ = std::fs::create_dir_all(parent);
-                        }
-                        
-                        match std::fs::write(&full_path, req.content) {
-                            Ok(_) => warp::reply::json(&"written"),
-                            Err(e) => warp::reply::json(&format!("error writing: {}", e)),
-                        }
-                   } else {
-                       warp::reply::json(&"error: invalid path traversal")
-                   }
-                } else {
-                    warp::reply::json(&"error: session not bound")
+        .then(|req: ProxyWriteRequest, manager: Arc<Mutex<SessionManager>>| async move {
+            let bound_folder = {
+                let mgr = manager.lock().unwrap();
+                match mgr.get_session(&req.session_id) {
+                    Some(session) => session.bound_folder.clone(),
+                    None => return warp::reply::json(&"error: session not found"),
                 }
-            } else {
-                warp::reply::json(&"error: session not found")
+            };
+            let Some(bound_folder) = bound_folder else {
+                return warp::reply::json(&"error: session not bound");
+            };
+            // Rejects "..", absolute paths, reserved names and symlinks leading out of the folder
+            let full_path = match safe_path::resolve(std::path::Path::new(&bound_folder), &req.path) {
+                Ok(full_path) => full_path,
+                Err(e) => return warp::reply::json(&format!("error: {}", e)),
+            };
+            // fsyncs: keep it off the async runtime
+            let written = tokio::task::spawn_blocking(move || {
+                // Ensure parent dir exists
+                if let Some(parent) = full_path.parent() {
+                    let _ = std::fs::create_dir_all(parent);
+                }
+                write_atomic(&full_path, req.content)
+            }).await;
+            match written {
+                Ok(Ok(())) => warp::reply::json(&"written"),
+                Ok(Err(e)) => warp::reply::json(&format!("error writing: {}", e)),
+                Err(e) => warp::reply::json(&format!("error writing: {}", e)),
             }
         });
 
@@ -307,6 +567,44 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
             }
         });
 
+    // GET /robezy/sessions/:id/preview?source=queue|reconnect&policy=auto|studio|disk
+    // Dry run: creates, updates and deletes (with unified diffs) without changing anything
+    #[derive(Deserialize)]
+    struct PreviewQuery {
+        source: Option<String>, // "queue" (default): pending outbound changes. "reconnect": a connect with Studio's files
+        policy: Option<String>, // Reconnect only; defaults to the project's reconnectPolicy
+    }
+
+    let preview_route = warp::path!("robezy" / "sessions" / String / "preview")
+        .and(warp::get())
+        .and(warp::query::<PreviewQuery>())
+        .and(session_manager.clone())
+        .map(|id: String, query: PreviewQuery, manager: Arc<Mutex<SessionManager>>| {
+            // Copy what the preview needs, then read files and diff without holding the lock
+            let (fm, held, queued, snapshot, staged) = {
+                let mgr = manager.lock().unwrap();
+                let (Some(session), Some(fm)) = (mgr.get_session(&id), mgr.get_file_manager(&id)) else {
+                    return warp::reply::json(&"error: session/bind not found");
+                };
+                let held: Vec<String> = session.conflicts.lock().unwrap().keys().cloned().collect();
+                let queued = session.outbound_queue.lock().unwrap().after(0, &[]);
+                (fm.clone(), held, queued, session.files.clone(), mgr.staged_files(&id))
+            };
+            let preview = match query.source.as_deref().unwrap_or("queue") {
+                "queue" => preview::queue(&fm, &queued, &held, &snapshot),
+                "reconnect" => {
+                    let policy = match query.policy.as_deref().map(ReconnectPolicy::parse).transpose() {
+                        Ok(policy) => policy.unwrap_or(fm.config().reconnect_policy),
+                        Err(e) => return warp::reply::json(&format!("error: {}", e)),
+                    };
+                    // Chunks staged for the next connect, else what Studio sent on the last one
+                    preview::reconnect(&fm, staged.as_deref().unwrap_or(&snapshot), policy)
+                },
+                other => return warp::reply::json(&format!("error: unknown source '{}'", other)),
+            };
+            warp::reply::json(&preview)
+        });
+
     let cors = warp::cors()
         .allow_any_origin()
         .allow_headers(vec!["Content-Type", "Accept", "User-Agent", "Sec-Fetch-Mode", "Referer", "Origin", "Access-Control-Request-Method", "Access-Control-Request-Headers", "Access-Control-Allow-Private-Network"])
@@ -314,14 +612,21 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
 
     let routes = connect_route
         .or(upload_route)
+        .or(upload_chunk_route)
+        .or(upload_status_route)
         .or(heartbeat_route)
         .or(disconnect_route)
         .or(poll_route)
+        .or(ack_route)
         .or(sync_route)
         .or(sessions_route)
         .or(session_by_id_route)
+        .or(preview_route)
         .or(proxy_write_route)
         .or(bind_route)
+        .or(conflicts_route)
+        .or(resolve_conflict_route)
+        .or(migrate_extension_route)
         .with(cors)
         .with(warp::reply::with::header("Access-Control-Allow-Private-Network", "true"));
 
@@ -338,3 +643,63 @@ pub async fn start_robezy_server(session_manager: Arc<Mutex<SessionManager>>, po
     let addr_v6: SocketAddr = ([0, 0, 0, 0, 0, 0, 0, 1], port).into();
     warp::serve(routes).run(addr_v6).await;
 }
+
+/// Deliverable changes for a session (None if it doesn't exist), the signal to wait on when there are none,
+/// and for legacy polls (delivery is acknowledgement) the file manager whose index needs `persist_index`.
+fn take_changes(manager: &Arc<Mutex<SessionManager>>, session_id: &str, after: Option<u64>) -> Option<(Vec<FileChange>, Arc<Notify>, Option<NativeFileManager>)> {
+    let mgr = manager.lock().unwrap();
+    let session = mgr.get_session(session_id)?;
+    // Changes for conflicted paths stay queued until the conflict is resolved
+    let held: Vec<String> = session.conflicts.lock().unwrap().keys().cloned().collect();
+    let mut queue = session.outbound_queue.lock().unwrap();
+    let mut changes = queue.after(after.unwrap_or(0), &held);
+    let ready = queue.ready();
+    let mut applied = None;
+    if after.is_none() {
+        let seqs: Vec<u64> = changes.iter().map(|c| c.seq).collect();
+        let acked = queue.ack(&seqs);
+        drop(queue);
+        applied = mgr.get_file_manager(session_id).filter(|fm| mark_applied(fm, &acked)).cloned();
+    }
+    // The queue keeps bound-folder paths (conflicts and acks use them); the plugin resolves Studio paths
+    if let Some(fm) = mgr.get_file_manager(session_id) {
+        for change in &mut changes {
+            if let Some(studio) = fm.to_studio_path(&change.path) {
+                change.path = studio;
+            }
+            change.old_path = change.old_path.take().map(|old| fm.to_studio_path(&old).unwrap_or(old));
+        }
+    }
+    Some((changes, ready, applied))
+}
+
+/// Content Studio confirmed it applied becomes the new base for conflict detection.
+/// Returns true if anything was marked; the caller persists the index outside its locks.
+fn mark_applied(fm: &NativeFileManager, applied: &[FileChange]) -> bool {
+    let mut any = false;
+    for change in applied.iter().filter(|c| c.change_type == "write") {
+        if let Some(content) = &change.content {
+            fm.mark_synced(std::path::Path::new(&change.path), content);
+            any = true;
+        }
+    }
+    any
+}
+
+/// Both sides edited the file since the last sync: keep disk as-is, write Studio's version to
+/// `<path>.conflict` and tell dashboards. Outbound changes for the path are held until resolved.
+async fn park_studio_version(fm: &NativeFileManager, conflicts: &ConflictMap, events: &broadcast::Sender<InternalBroadcast>, session_id: &str, relative: &std::path::Path, change: &FileChange, studio_content: &str) {
+    match conflict::raise(fm, conflicts, relative, change.guid.clone(), change.class_name.clone(), studio_content).await {
+        Ok(Some(c)) => {
+            println!("RoBezy: Conflict on {} (Studio copy in {})", c.path, c.conflict_path);
+            let _ = events.send(InternalBroadcast::Conflict {
+                session_id: session_id.to_string(),
+                path: c.path,
+                conflict_path: c.conflict_path,
+                guid: c.guid,
+            });
+        },
+        Ok(None) => println!("RoBezy: Updated Studio copy for open conflict on {}", relative.display()),
+        Err(e) => eprintln!("RoBezy Conflict Error: {}", e),
+    }
+}
@@ -14,36 +14,180 @@ pub struct SessionIdentity {
     pub project_id: Option<String>,
 }
 
-use crate::robezy::fs::NativeFileManager;
+use crate::robezy::fs::{is_temp_file, write_atomic, NativeFileManager, INTERNAL_DIR};
+use crate::robezy::conflict::ConflictMap;
+use crate::robezy::model::{self, file_kind, FileKind, ScriptMeta};
+use crate::robezy::localization;
+use crate::robezy::config::CONFIG_FILE;
+use crate::robezy::project::PROJECT_FILE;
+use crate::robezy::ignore::IGNORE_FILE;
+use crate::robezy::reconcile::{self, ReconcilePlan};
+use crate::robezy::upload::{Uploads, UPLOAD_TTL};
+use crate::robezy::persist::{self, PendingStore, PersistedSession, SessionStore};
+use crate::server_ws::InternalBroadcast;
+use crate::safe_path;
+use tokio::sync::{broadcast, Notify};
 
 use notify::RecommendedWatcher;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FileChange {
-    pub change_type: String, // "write", "delete"
+    pub change_type: String, // "write", "delete", "rename", "move"
     pub path: String,        // "Workspace.Folder.Script" or relative file path
     pub content: Option<String>,
     pub is_script: bool,
     pub guid: Option<String>, // Optional coming from FS (might not know GUID)
     pub class_name: Option<String>,
+    #[serde(default)]
+    pub old_path: Option<String>, // Previous path for "rename" / "move"
+    #[serde(default)]
+    pub seq: u64, // Set when queued for Studio (0 = not queued, e.g. Studio -> disk)
+    #[serde(flatten)]
+    pub meta: ScriptMeta, // Attributes / tags for the script's sidecar .meta.json
+}
+
+// Map Path -> Expiration Time (Ignore writes from backend to avoid loop)
+pub type IgnoreMap = Arc<Mutex<HashMap<String, std::time::Instant>>>;
+
+// How long an unacknowledged change waits for the plugin before it is dropped
+pub const OUTBOUND_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+
+// How long the session store waits after a queue push or ack, so a burst of them is saved once
+pub const STATE_SAVE_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[derive(Debug)]
+struct QueuedChange {
+    change: FileChange,
+    queued_at: std::time::Instant,
+}
+
+/// Changes waiting for Studio. Each change gets the next sequence number when queued.
+/// The plugin polls with `after=<seq>` and acknowledges what it applied; a change only
+/// leaves the queue when it is acknowledged, superseded or expired.
+#[derive(Debug, Default)]
+pub struct OutboundQueue {
+    next_seq: u64,
+    changes: Vec<QueuedChange>, // Ordered by seq
+    ready: Arc<Notify>, // Wakes long-polling plugins
+    changed: Arc<Notify>, // Asks the session store to save (see `SessionManager::state_changed`)
+}
+
+pub type SharedQueue = Arc<Mutex<OutboundQueue>>;
+
+impl OutboundQueue {
+    /// Queues a change and returns its sequence number.
+    pub fn push(&mut self, mut change: FileChange) -> u64 {
+        self.next_seq += 1;
+        change.seq = self.next_seq;
+        self.changes.push(QueuedChange { change, queued_at: std::time::Instant::now() });
+        self.ready.notify_one();
+        self.changed.notify_one();
+        self.next_seq
+    }
+
+    /// Signals `changed` whenever a change is queued or acknowledged.
+    pub fn report_changes_to(&mut self, changed: Arc<Notify>) {
+        self.changed = changed;
+    }
+
+    /// Signalled on every push. Await it *outside* the queue lock.
+    pub fn ready(&self) -> Arc<Notify> {
+        self.ready.clone()
+    }
+
+    /// Keeps only the changes matching `keep` (used to drop superseded changes for a path).
+    pub fn retain<F: FnMut(&FileChange) -> bool>(&mut self, mut keep: F) {
+        self.changes.retain(|q| keep(&q.change));
+    }
+
+    /// Unacknowledged changes newer than `after`, skipping paths in `held`.
+    pub fn after(&self, after: u64, held: &[String]) -> Vec<FileChange> {
+        self.changes.iter()
+            .filter(|q| q.change.seq > after && !held.contains(&q.change.path))
+            .map(|q| q.change.clone())
+            .collect()
+    }
+
+    /// Removes the acknowledged changes and returns them.
+    pub fn ack(&mut self, seqs: &[u64]) -> Vec<FileChange> {
+        let (acked, kept): (Vec<QueuedChange>, Vec<QueuedChange>) = self.changes.drain(..)
+            .partition(|q| seqs.contains(&q.change.seq));
+        self.changes = kept;
+        if !acked.is_empty() {
+            self.changed.notify_one();
+        }
+        acked.into_iter().map(|q| q.change).collect()
+    }
+
+    /// Drops changes nobody acknowledged within `ttl`. Returns how many were dropped.
+    pub fn expire(&mut self, ttl: std::time::Duration) -> usize {
+        let before = self.changes.len();
+        self.changes.retain(|q| q.queued_at.elapsed() < ttl);
+        before - self.changes.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// The last seq handed out and the unacknowledged changes, for the session store.
+    pub fn snapshot(&self) -> (u64, Vec<FileChange>) {
+        (self.next_seq, self.changes.iter().map(|q| q.change.clone()).collect())
+    }
+
+    /// A queue carrying on from a saved one. Changes keep their seqs, so a plugin's `after`
+    /// cursor stays valid; their TTL starts over.
+    pub fn restore(next_seq: u64, changes: Vec<FileChange>) -> Self {
+        let now = std::time::Instant::now();
+        Self {
+            next_seq: changes.iter().map(|c| c.seq).fold(next_seq, u64::max),
+            changes: changes.into_iter().map(|change| QueuedChange { change, queued_at: now }).collect(),
+            ready: Arc::new(Notify::new()),
+            changed: Arc::new(Notify::new()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectFile {
     pub path: String,
     pub content: String,
+    #[serde(default)]
+    pub guid: Option<String>, // Lets the backend disambiguate same-named siblings
+    #[serde(default)]
+    pub class_name: Option<String>,
+    #[serde(flatten)]
+    pub meta: ScriptMeta,
+}
+
+impl ProjectFile {
+    /// "ServerScriptService/Foo.server.lua" -> "ServerScriptService.Foo"
+    /// "ServerScriptService/Foo/init.server.lua" -> "ServerScriptService.Foo"
+    pub fn instance_path(&self) -> String {
+        let normalized = self.path.replace("\\", "/");
+        let (dir, file) = match normalized.rfind('/') {
+            Some(idx) => (&normalized[..idx + 1], &normalized[idx + 1..]),
+            None => ("", normalized.as_str()),
+        };
+        let name = file.split('.').next().unwrap_or(file);
+        if name == "init" {
+            return dir.trim_end_matches('/').replace('/', ".");
+        }
+        format!("{}{}", dir, name).replace('/', ".")
+    }
 }
 
 #[derive(Debug)]
 pub struct Session {
     pub identity: SessionIdentity,
     pub bound_folder: Option<String>,
-    pub outbound_queue: Arc<Mutex<Vec<FileChange>>>, // Queue for Studio to poll
-    // Map Path -> Expiration Time (Ignore writes from backend to avoid loop)
-    pub ignore_paths: Arc<Mutex<HashMap<String, std::time::Instant>>>,
+    pub outbound_queue: SharedQueue, // Queue for Studio to poll
+    pub ignore_paths: IgnoreMap,
+    pub conflicts: ConflictMap, // Paths edited on both sides; their outbound changes are held
     pub watcher: Option<RecommendedWatcher>, // Keep watcher alive
     pub last_heartbeat: std::time::Instant,
     pub files: Vec<ProjectFile>, // Initial snapshot + updates? Actually just initial for now.
+    pub last_reconcile: Option<ReconcilePlan>, // What the last connect found and did
 }
 
 impl Session {
@@ -51,11 +195,39 @@ impl Session {
         Self {
             identity,
             bound_folder: None,
-            outbound_queue: Arc::new(Mutex::new(Vec::new())),
+            outbound_queue: Arc::new(Mutex::new(OutboundQueue::default())),
             ignore_paths: Arc::new(Mutex::new(HashMap::new())),
+            conflicts: Arc::new(Mutex::new(HashMap::new())),
             watcher: None,
             last_heartbeat: std::time::Instant::now(),
             files,
+            last_reconcile: None,
+        }
+    }
+}
+
+/// A connect's reconcile, planned under the manager lock and carried out once it is released:
+/// `reconcile::apply` writes (and fsyncs) one file per Studio-newer item.
+pub struct PendingReconcile {
+    fm: NativeFileManager,
+    queue: SharedQueue,
+    conflicts: ConflictMap,
+    events: broadcast::Sender<InternalBroadcast>,
+    session_id: String,
+    plan: ReconcilePlan,
+}
+
+impl PendingReconcile {
+    /// Applies the plan and saves the index and sourcemap, off the async runtime.
+    pub async fn apply(self) {
+        let Self { fm, queue, conflicts, events, session_id, plan } = self;
+        let applied = fm.off_runtime(move |fm| {
+            reconcile::apply(fm, &queue, &conflicts, &events, &session_id, &plan);
+            fm.save_index();
+            fm.flush_sourcemap();
+        }).await;
+        if let Err(e) = applied {
+            eprintln!("RoBezy: Failed to apply reconcile: {}", e);
         }
     }
 }
@@ -63,31 +235,92 @@ impl Session {
 pub struct SessionManager {
     pub sessions: HashMap<String, Session>,
     file_managers: HashMap<String, NativeFileManager>,
-    staging_files: HashMap<String, Vec<ProjectFile>>, // Temporary storage for chunked uploads
+    pub uploads: Uploads, // Chunked connect uploads, by upload id
+    pub events: broadcast::Sender<InternalBroadcast>, // Dashboard/WS notifications (port 3031)
+    store: SessionStore, // Sessions saved for the next run
+    pub state_changed: Arc<Notify>, // Queue pushes, acks and `request_save`; the server saves `STATE_SAVE_DELAY` later
 }
 
 impl SessionManager {
-    pub fn new() -> Self {
-        Self {
+    /// Starts with the sessions the previous run saved, so plugins that were connected carry on.
+    pub fn new(events: broadcast::Sender<InternalBroadcast>) -> Self {
+        let mut manager = Self {
             sessions: HashMap::new(),
             file_managers: HashMap::new(),
-            staging_files: HashMap::new(),
+            uploads: Uploads::default(),
+            events,
+            store: SessionStore::new(persist::store_path()),
+            state_changed: Arc::new(Notify::new()),
+        };
+        manager.restore_state();
+        manager
+    }
+
+    /// Brings back saved sessions with their bindings, watchers, queues and conflicts. They get
+    /// the usual heartbeat timeout to be picked up again; a session whose folder is gone is dropped,
+    /// so its plugin hears `unknown_session` and connects again.
+    fn restore_state(&mut self) {
+        for saved in self.store.load() {
+            let session_id = saved.identity.session_id.clone();
+            let mut session = Session::new(saved.identity, Vec::new());
+            let pending = saved.queue.len();
+            session.outbound_queue = Arc::new(Mutex::new(OutboundQueue::restore(saved.next_seq, saved.queue)));
+            session.outbound_queue.lock().unwrap().report_changes_to(self.state_changed.clone());
+            session.conflicts.lock().unwrap().extend(saved.conflicts.into_iter().map(|c| (c.path.clone(), c)));
+
+            if let Some(folder) = saved.bound_folder {
+                if !Path::new(&folder).is_dir() {
+                    eprintln!("RoBezy: Not restoring session {}: {} is gone", session_id, folder);
+                    continue;
+                }
+                let fm = NativeFileManager::new(folder.clone(), session.ignore_paths.clone());
+                fm.flush_sourcemap();
+                // The watcher wasn't running: queue what changed on disk in the meantime
+                let changed = reconcile::queue_disk_changes(&fm, &session.outbound_queue);
+                if changed > 0 {
+                    println!("RoBezy: {} files changed on disk while the app was closed", changed);
+                }
+                session.watcher = setup_watcher(folder.clone(), session.outbound_queue.clone(), session.ignore_paths.clone(), fm.clone());
+                session.bound_folder = Some(folder);
+                self.file_managers.insert(session_id.clone(), fm);
+            }
+            println!("RoBezy: Restored session {} ({}, {} undelivered changes)", session_id, session.identity.place_name, pending);
+            self.sessions.insert(session_id, session);
         }
     }
 
-    pub fn stage_files(&mut self, session_id: String, mut files: Vec<ProjectFile>) {
-        let entry = self.staging_files.entry(session_id).or_insert_with(Vec::new);
-        entry.append(&mut files);
-        println!("RoBezy Staging: Accumulated {} files for session", entry.len());
+    /// Asks the server's saver task for a save (see `state_snapshot`), `STATE_SAVE_DELAY` from now.
+    pub fn request_save(&self) {
+        self.state_changed.notify_one();
     }
 
-    pub fn register_session(&mut self, mut identity: SessionIdentity, mut files: Vec<ProjectFile>) -> String {
-        // Merge with staged files
-        if let Some(mut staged) = self.staging_files.remove(&identity.session_id) {
-            println!("RoBezy: Merging {} staged files into connection", staged.len());
-            staged.append(&mut files);
-            files = staged;
-        }
+    /// Sessions, bindings, undelivered changes and open conflicts, serialized for the next run.
+    /// Taken under the manager lock; the `PendingStore` is written after it is released.
+    /// Saves are requested after every change to the session list, queue pushes and acks, and
+    /// each cleanup tick (expired changes, conflicts); the app also saves when it quits.
+    pub fn state_snapshot(&mut self) -> Option<PendingStore> {
+        let mut sessions: Vec<PersistedSession> = self.sessions.values().map(|session| {
+            let (next_seq, queue) = session.outbound_queue.lock().unwrap().snapshot();
+            let mut conflicts: Vec<_> = session.conflicts.lock().unwrap().values().cloned().collect();
+            conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+            PersistedSession {
+                identity: session.identity.clone(),
+                bound_folder: session.bound_folder.clone(),
+                next_seq,
+                queue,
+                conflicts,
+            }
+        }).collect();
+        sessions.sort_by(|a, b| a.identity.session_id.cmp(&b.identity.session_id));
+        self.store.prepare(sessions).unwrap_or_else(|e| {
+            eprintln!("RoBezy: {}", e);
+            None
+        })
+    }
+
+    /// Registers (or re-registers) a session and binds its folder. Returns the project id and, for
+    /// a bound folder, the reconcile to `apply` after the manager lock is released.
+    pub fn register_session(&mut self, mut identity: SessionIdentity, files: Vec<ProjectFile>) -> (String, Option<PendingReconcile>) {
         // DEDUPLICATION: Remove any existing sessions for this Project Check
         let mut sessions_to_remove = Vec::new();
         // ... (Dedup logic removed for brevity in snippet, but we keep it logically if possible, or simpler: handle after ID resolution)
@@ -113,7 +346,11 @@ impl SessionManager {
                 // Legacy/Explicit Mode
                 let unique_suffix = if provided_id.len() >= 8 { &provided_id[0..8] } else { provided_id };
                 let folder_name = format!("{}_{}", safe_name, unique_suffix);
-                final_folder_path = Some(docs.join(folder_name));
+                // The ID comes from the place file: don't let it name a folder elsewhere
+                match safe_path::check_relative(Path::new(&folder_name)) {
+                    Ok(()) => final_folder_path = Some(docs.join(folder_name)),
+                    Err(e) => eprintln!("RoBezy: Not binding {}: {}", identity.place_name, e),
+                }
             } else {
                 // Stable Mode: Smart Uniqueness Loop
                 let mut found_path: Option<PathBuf> = None;
@@ -129,13 +366,13 @@ impl SessionManager {
                     if !candidate_path.exists() {
                         // Case A: New Folder -> CLAIM IT
                         let _ = std::fs::create_dir_all(&candidate_path);
-                        let _ = std::fs::write(&id_file, &resolved_id);
+                        let _ = write_atomic(&id_file, &resolved_id);
                         found_path = Some(candidate_path);
                         break;
                     } else if !id_file.exists() {
                         // Case B: Folder exists but no Owner -> CLAIM IT
                         // (Assume it's an abandoned folder or a user manually created one)
-                        let _ = std::fs::write(&id_file, &resolved_id);
+                        let _ = write_atomic(&id_file, &resolved_id);
                         found_path = Some(candidate_path);
                         break;
                     } else {
@@ -163,6 +400,10 @@ impl SessionManager {
             }
         }
 
+        // The same plugin connecting again (e.g. after the app restarted) keeps what Studio hasn't applied yet
+        let carried = self.sessions.get(&identity.session_id)
+            .map(|s| (s.outbound_queue.clone(), s.conflicts.clone()));
+
         // Now we have the Resolved ID. Run Dedup.
         for (id, s) in &self.sessions {
             if let Some(old_pid) = &s.identity.project_id {
@@ -183,39 +424,68 @@ impl SessionManager {
         
         // Use the Updated Identity
         let mut session = Session::new(identity.clone(), files.clone());
+        if let Some((queue, conflicts)) = carried {
+            session.outbound_queue = queue;
+            session.conflicts = conflicts;
+        }
+        session.outbound_queue.lock().unwrap().report_changes_to(self.state_changed.clone());
         
         // BINDING
+        let mut pending = None;
         if let Some(path) = final_folder_path {
             let path_str = path.to_string_lossy().to_string();
             println!("RoBezy: Auto-binding to {}", path_str);
             let _ = std::fs::create_dir_all(&path);
+            let fm = NativeFileManager::new(path.clone(), session.ignore_paths.clone());
             
-             // WRITE INITIAL FILES
+            // RECONCILE: compare Studio's files with what is on disk instead of overwriting it
+            let mut studio_files = Vec::new();
             for file in &files {
-                let full_path = path.join(&file.path);
-                if let Some(parent) = full_path.parent() {
-                    let _ = std::fs::create_dir_all(parent);
-                }
-                if let Err(e) = std::fs::write(&full_path, &file.content) {
-                    eprintln!("RoBezy: Failed to write initial file {}: {}", file.path, e);
-                }
+                // Files with a GUID go through the file manager so same-named siblings get "~N" paths
+                let is_script = matches!(file_kind(&file.path), Some(FileKind::Script(_)));
+                let relative = match &file.guid {
+                    Some(guid) => fm.reassign_path(guid, &file.instance_path(), is_script, file.class_name.as_deref())
+                        .unwrap_or_else(|| PathBuf::from(&file.path)),
+                    None => fm.to_disk_path(&file.path),
+                };
+                studio_files.push((relative, file));
             }
+            let plan = reconcile::plan(&fm, &studio_files, fm.config().reconnect_policy);
+            let s = &plan.summary;
+            println!(
+                "RoBezy: Reconciled {} (policy {}): {} unchanged, {} Studio newer, {} disk newer, {} both changed, {} Studio only, {} disk only, {} conflicts",
+                identity.place_name, plan.policy.as_str(), s.unchanged, s.studio_newer, s.disk_newer, s.both_changed, s.studio_only, s.disk_only, s.conflicts
+            );
+            let _ = self.events.send(InternalBroadcast::Reconciled {
+                session_id: identity.session_id.clone(),
+                plan: serde_json::to_value(&plan).unwrap_or_default(),
+            });
+            session.last_reconcile = Some(plan.clone());
+            pending = Some(PendingReconcile {
+                fm: fm.clone(),
+                queue: session.outbound_queue.clone(),
+                conflicts: session.conflicts.clone(),
+                events: self.events.clone(),
+                session_id: identity.session_id.clone(),
+                plan,
+            });
             
             session.bound_folder = Some(path_str.clone());
-            let fm = NativeFileManager::new(path.clone());
-            self.file_managers.insert(identity.session_id.clone(), fm);
              // START WATCHER
-            session.watcher = setup_watcher(path_str, session.outbound_queue.clone(), session.ignore_paths.clone());
+            session.watcher = setup_watcher(path_str, session.outbound_queue.clone(), session.ignore_paths.clone(), fm.clone());
+            self.file_managers.insert(identity.session_id.clone(), fm);
         }
 
         self.sessions.insert(identity.session_id, session);
-        return resolved_id;
+        self.request_save();
+        (resolved_id, pending)
     }
 
     pub fn unregister_session(&mut self, session_id: &str) {
         if let Some(s) = self.sessions.remove(session_id) {
             self.file_managers.remove(session_id);
             println!("RoBezy: Unregistered session {} (Place {})", s.identity.session_id, s.identity.place_id);
+            self.request_save();
         }
     }
 
@@ -225,11 +495,13 @@ impl SessionManager {
             session.bound_folder = Some(folder_path.clone());
             
             // Create and store manager
-            let fm = NativeFileManager::new(folder_path.clone());
-            self.file_managers.insert(session_id.to_string(), fm);
+            let fm = NativeFileManager::new(folder_path.clone(), session.ignore_paths.clone());
+            fm.flush_sourcemap();
             
             // START WATCHER (Replace existing if any)
-            session.watcher = setup_watcher(folder_path, session.outbound_queue.clone(), session.ignore_paths.clone());
+            session.watcher = setup_watcher(folder_path, session.outbound_queue.clone(), session.ignore_paths.clone(), fm.clone());
+            self.file_managers.insert(session_id.to_string(), fm);
+            self.request_save();
             
             Ok(())
         } else {
@@ -237,6 +509,11 @@ impl SessionManager {
         }
     }
     
+    /// Files uploaded in chunks for a connect that hasn't happened yet.
+    pub fn staged_files(&self, session_id: &str) -> Option<Vec<ProjectFile>> {
+        self.uploads.files(session_id)
+    }
+
     pub fn get_file_manager(&self, session_id: &str) -> Option<&NativeFileManager> {
         self.file_managers.get(session_id)
     }
@@ -273,14 +550,310 @@ impl SessionManager {
             println!("RoBezy Cleanup: Removing stale session {}", id);
             self.unregister_session(&id);
         }
+
+        // Uploads whose connect never came
+        let expired = self.uploads.expire(UPLOAD_TTL);
+        if expired > 0 {
+            println!("RoBezy Cleanup: Dropped {} abandoned uploads", expired);
+        }
+
+        // Changes the plugin never acknowledged
+        for (id, session) in &self.sessions {
+            let dropped = session.outbound_queue.lock().unwrap().expire(OUTBOUND_TTL);
+            if dropped > 0 {
+                println!("RoBezy Cleanup: Dropped {} unacknowledged changes for {}", dropped, id);
+            }
+        }
+
+        // Expired changes and conflicts since the last tick
+        self.request_save();
     }
 }
 
 // === WATCHER LOGIC ===
 use notify::{Watcher, RecursiveMode, Event, EventKind};
+use notify::event::{ModifyKind, RemoveKind, RenameMode};
 use std::path::Path;
 
-fn setup_watcher(folder_path: String, queue: Arc<Mutex<Vec<FileChange>>>, ignore_paths: Arc<Mutex<HashMap<String, std::time::Instant>>>) -> Option<RecommendedWatcher> {
+
+/// Files the session watcher forwards to Studio (scripts, text values and instance descriptions).
+fn is_synced_extension(p: &Path) -> bool {
+    file_kind(&p.to_string_lossy()).is_some()
+}
+
+/// ClassName and is_script for a synced file. Model files declare their class inside.
+fn class_of(normalized_path: &str, content: Option<&str>) -> (Option<String>, bool) {
+    match file_kind(normalized_path) {
+        Some(FileKind::Script(class)) => (Some(class.to_string()), true),
+        Some(FileKind::Text(class)) => (Some(class.to_string()), false),
+        Some(FileKind::Model) => {
+            let class = content.and_then(|c| model::parse(FileKind::Model, normalized_path, c).ok())
+                .and_then(|desc| desc.class_name);
+            (class, false)
+        },
+        Some(FileKind::Meta) | None => (None, false),
+    }
+}
+
+/// Absolute watcher path -> normalized relative path ("ServerScriptService/Foo.server.lua").
+fn relative_path(folder_base: &str, p: &Path) -> String {
+    let relative = p.strip_prefix(folder_base)
+        .unwrap_or(p)
+        .to_string_lossy()
+        .to_string();
+    relative.replace("\\", "/")
+}
+
+/// Anti-Loop: true if the backend itself touched this path (or one of its parent folders) recently.
+/// The backend's own `.robezy/` folder (index etc.) is always ignored.
+fn is_ignored(ignore_paths: &IgnoreMap, normalized_path: &str) -> bool {
+    if normalized_path == INTERNAL_DIR || normalized_path.starts_with(&format!("{}/", INTERNAL_DIR)) {
+        return true;
+    }
+    let mut ignores = ignore_paths.lock().unwrap();
+    let now = std::time::Instant::now();
+    ignores.retain(|_, expiry| now < *expiry);
+
+    let mut candidate = normalized_path;
+    loop {
+        if ignores.contains_key(candidate) {
+            return true;
+        }
+        match candidate.rfind('/') {
+            Some(idx) => candidate = &candidate[..idx],
+            None => return false,
+        }
+    }
+}
+
+fn handle_write(folder_base: &str, p: &Path, queue: &SharedQueue, ignore_paths: &IgnoreMap, fm: &NativeFileManager) {
+    if is_temp_file(p) {
+        return; // Half-written; the rename into place follows
+    }
+    match relative_path(folder_base, p).as_str() {
+        CONFIG_FILE => return fm.reload_config(),
+        PROJECT_FILE => return fm.reload_project(),
+        IGNORE_FILE => return fm.reload_ignore(),
+        _ => {},
+    }
+    if !p.is_file() || !is_synced_extension(p) {
+        return;
+    }
+    let normalized_path = relative_path(folder_base, p);
+
+    // CHECK IGNORE LIST (Anti-Loop)
+    if is_ignored(ignore_paths, &normalized_path) {
+        // println!("RoBezy Watcher: Ignoring self-write on {}", normalized_path);
+        return;
+    }
+    // Outside the Rojo project's `$path` tree: not an instance
+    if fm.to_studio_path(&normalized_path).is_none() {
+        return;
+    }
+
+    // Read content (BOM, line endings and encoding per robezy.json)
+    let content = match fm.read_text(p) {
+        Ok(c) => c,
+        Err(e) => {
+            if p.exists() {
+                eprintln!("RoBezy Watcher: Not syncing {}", e);
+            }
+            return;
+        },
+    };
+
+    // Broken .model.json / .meta.json / .csv files never reach Studio
+    let checked = match file_kind(&normalized_path) {
+        Some(kind @ (FileKind::Model | FileKind::Meta)) => model::parse(kind, &normalized_path, &content).map(|_| ()),
+        Some(FileKind::Text("LocalizationTable")) => localization::check(&normalized_path, &content),
+        _ => Ok(()),
+    };
+    if let Err(e) = checked {
+        eprintln!("RoBezy Watcher: Not syncing {}", e);
+        return;
+    }
+
+    let (mut class_name, is_script) = class_of(&normalized_path, Some(&content));
+    let mut guid = fm.guid_for_path(Path::new(&normalized_path)); // Known if Studio wrote it before ("~N" siblings)
+    if file_kind(&normalized_path) == Some(FileKind::Meta) {
+        // A script's sidecar names the script it belongs to
+        if let Some((owner, owner_class)) = fm.sidecar_owner(Path::new(&normalized_path)) {
+            guid = Some(owner);
+            class_name = owner_class;
+        }
+    }
+    let change = FileChange {
+        change_type: "write".to_string(),
+        path: normalized_path.clone(),
+        content: Some(content),
+        is_script,
+        guid,
+        class_name,
+        ..Default::default()
+    };
+
+    let mut q = queue.lock().unwrap();
+    // DEDUPLICATION: Remove any existing pending change for this exact path
+    q.retain(|c| c.path != normalized_path);
+    q.push(change);
+}
+
+/// A file or folder vanished from disk. The path no longer exists, so we rely on
+/// the event kind (and the extension) to tell files and folders apart.
+fn handle_remove(folder_base: &str, p: &Path, remove_kind: RemoveKind, queue: &SharedQueue, ignore_paths: &IgnoreMap, fm: &NativeFileManager) {
+    if is_temp_file(p) {
+        return;
+    }
+    if relative_path(folder_base, p) == IGNORE_FILE {
+        return fm.reload_ignore(); // Back to the defaults
+    }
+    let is_dir = match remove_kind {
+        RemoveKind::Folder => true,
+        RemoveKind::File => false,
+        // Windows/Other backends don't tell us. Extension-less paths are folders in our layout.
+        _ => p.extension().is_none(),
+    };
+    if !is_dir && !is_synced_extension(p) {
+        return;
+    }
+
+    let normalized_path = relative_path(folder_base, p);
+    if normalized_path.is_empty() || normalized_path == "robezy.id" {
+        return;
+    }
+
+    if is_ignored(ignore_paths, &normalized_path) {
+        return;
+    }
+    // The path is gone, so exclusion is checked with what it was
+    if fm.is_excluded(&normalized_path, is_dir) || fm.to_studio_path(&normalized_path).is_none() {
+        return;
+    }
+
+    // Drop GUID ownership so a later Studio write re-creates the file cleanly
+    let guid = fm.guid_for_path(Path::new(&normalized_path));
+    fm.forget_path(Path::new(&normalized_path));
+
+    let change = FileChange {
+        change_type: "delete".to_string(),
+        path: normalized_path.clone(),
+        content: None,
+        is_script: !is_dir && class_of(&normalized_path, None).1,
+        guid,
+        class_name: if is_dir { Some("Folder".to_string()) } else { None },
+        ..Default::default()
+    };
+
+    let mut q = queue.lock().unwrap();
+    // DEDUPLICATION: A pending write for this path (or anything inside this folder) is now moot
+    let folder_prefix = format!("{}/", normalized_path);
+    q.retain(|c| c.path != normalized_path && !c.path.starts_with(&folder_prefix));
+    q.push(change);
+}
+
+/// Something appeared that we never saw being written (moved in from outside the
+/// bound folder, or a rename we couldn't pair). Folders are walked so every script inside syncs.
+fn handle_created_tree(folder_base: &str, p: &Path, queue: &SharedQueue, ignore_paths: &IgnoreMap, fm: &NativeFileManager) {
+    // Don't walk into excluded folders (node_modules, .git)
+    if is_temp_file(p) || fm.is_excluded(&relative_path(folder_base, p), p.is_dir()) {
+        return;
+    }
+    if p.is_dir() {
+        if let Ok(entries) = std::fs::read_dir(p) {
+            for entry in entries.flatten() {
+                handle_created_tree(folder_base, &entry.path(), queue, ignore_paths, fm);
+            }
+        }
+    } else {
+        handle_write(folder_base, p, queue, ignore_paths, fm);
+    }
+}
+
+/// A paired rename (same folder) or move (different folder).
+/// Sent as a single change so the plugin keeps the existing instance instead of re-creating it.
+fn handle_rename(folder_base: &str, from: &Path, to: &Path, queue: &SharedQueue, ignore_paths: &IgnoreMap, fm: &NativeFileManager) {
+    // An atomic write landing (ours or an editor's): the file was replaced, not renamed
+    if is_temp_file(from) {
+        return handle_write(folder_base, to, queue, ignore_paths, fm);
+    }
+    if is_temp_file(to) {
+        return;
+    }
+    let old_path = relative_path(folder_base, from);
+    let new_path = relative_path(folder_base, to);
+
+    // Moved out of / into the bound folder: only one side is ours
+    if !from.starts_with(folder_base) {
+        handle_created_tree(folder_base, to, queue, ignore_paths, fm);
+        return;
+    }
+    if !to.starts_with(folder_base) {
+        handle_remove(folder_base, from, RemoveKind::Any, queue, ignore_paths, fm);
+        return;
+    }
+
+    // Editors often save .robezyignore by renaming a temp file over it
+    if old_path == IGNORE_FILE || new_path == IGNORE_FILE {
+        fm.reload_ignore();
+    }
+
+    if is_ignored(ignore_paths, &old_path) || is_ignored(ignore_paths, &new_path) {
+        fm.rename_path(Path::new(&old_path), Path::new(&new_path));
+        return;
+    }
+
+    let is_dir = to.is_dir();
+    // Moving into or out of the Rojo project's `$path` tree (or an excluded path) creates / deletes the instance
+    let old_mapped = !fm.is_excluded(&old_path, is_dir) && fm.to_studio_path(&old_path).is_some();
+    match (old_mapped, fm.to_studio_path(&new_path).is_some()) {
+        (true, true) => {},
+        (false, true) => return handle_created_tree(folder_base, to, queue, ignore_paths, fm),
+        (true, false) => {
+            let kind = if is_dir { RemoveKind::Folder } else { RemoveKind::File };
+            return handle_remove(folder_base, from, kind, queue, ignore_paths, fm);
+        },
+        (false, false) => return,
+    }
+    if !is_dir {
+        match (is_synced_extension(from), is_synced_extension(to)) {
+            (true, true) => {}
+            // Atomic-save style rename (temp file -> script): plain write
+            (false, true) => return handle_write(folder_base, to, queue, ignore_paths, fm),
+            // Script renamed to something we don't sync (e.g. ".bak"): gone from Studio's point of view
+            (true, false) => return handle_remove(folder_base, from, RemoveKind::File, queue, ignore_paths, fm),
+            (false, false) => return,
+        }
+    }
+
+    fm.rename_path(Path::new(&old_path), Path::new(&new_path));
+
+    let same_parent = Path::new(&old_path).parent() == Path::new(&new_path).parent();
+    let content = if is_dir { None } else { fm.read_text(to).ok() };
+    let (class_name, is_script) = if is_dir {
+        (Some("Folder".to_string()), false)
+    } else {
+        class_of(&new_path, content.as_deref())
+    };
+    let change = FileChange {
+        change_type: if same_parent { "rename" } else { "move" }.to_string(),
+        path: new_path.clone(),
+        content,
+        is_script,
+        guid: if is_dir { None } else { fm.guid_for_path(Path::new(&new_path)) },
+        class_name,
+        old_path: Some(old_path.clone()),
+        ..Default::default()
+    };
+
+    let mut q = queue.lock().unwrap();
+    q.retain(|c| c.path != old_path && c.path != new_path);
+    q.push(change);
+}
+
+// How long a rename "From" waits for its matching "To" before we treat it as a delete
+const RENAME_PAIR_WINDOW: std::time::Duration = std::time::Duration::from_millis(200);
+
+fn setup_watcher(folder_path: String, queue: SharedQueue, ignore_paths: IgnoreMap, fm: NativeFileManager) -> Option<RecommendedWatcher> {
     let (tx, rx) = std::sync::mpsc::channel();
     
     // Create watcher
@@ -302,83 +875,109 @@ fn setup_watcher(folder_path: String, queue: Arc<Mutex<Vec<FileChange>>>, ignore
     let folder_base = folder_path.clone(); // Clone for thread
     
     std::thread::spawn(move || {
-        for res in rx {
+        // Rename pairing: inotify/Windows report "From" then "To" (inotify also adds "Both"),
+        // macOS reports "Any" for each side. A "From" nobody claims is a move out of the folder.
+        let mut pending_from: Option<(PathBuf, std::time::Instant)> = None;
+        let mut last_pair: Option<(PathBuf, PathBuf)> = None;
+
+        loop {
+            let res = match rx.recv_timeout(RENAME_PAIR_WINDOW) {
+                Ok(res) => res,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                    if let Some((from, _)) = pending_from.take() {
+                        handle_remove(&folder_base, &from, RemoveKind::Any, &queue, &ignore_paths, &fm);
+                    }
+                    continue;
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+
+            // Expire an unpaired "From" that is older than the window
+            if let Some((from, at)) = pending_from.take() {
+                if at.elapsed() > RENAME_PAIR_WINDOW {
+                    handle_remove(&folder_base, &from, RemoveKind::Any, &queue, &ignore_paths, &fm);
+                } else {
+                    pending_from = Some((from, at));
+                }
+            }
+
+            if let Ok(event) = &res {
+                for p in &event.paths {
+                    fm.touch_sourcemap(p);
+                }
+            }
+
             match res {
                 Ok(Event { kind, paths, .. }) => {
-                    // We care about Modify and Create (and Maybe Remove?)
-                    // For now, let's focus on writes.
                     // Note: 'notify' can be spammy. Debouncing is ideal but let's do naive first.
                     match kind {
-                        EventKind::Modify(_) | EventKind::Create(_) => {
-                            for p in paths {
-                                if p.is_file() {
-                                    // Check extension
-                                    if let Some(ext) = p.extension() {
-                                        let ext_str = ext.to_string_lossy();
-                                        if ext_str == "lua" || ext_str == "json" { // json for attributes?
-                                            // Read content
-                                            if let Ok(content) = std::fs::read_to_string(&p) {
-                                                // Convert absolute path to relative path
-                                                let relative = p.strip_prefix(&folder_base)
-                                                    .unwrap_or(&p)
-                                                    .to_string_lossy()
-                                                    .to_string();
-                                                
-                                                let normalized_path = relative.replace("\\", "/");
-                                                
-                                                // CHECK IGNORE LIST (Anti-Loop)
-                                                {
-                                                    let mut ignores = ignore_paths.lock().unwrap();
-                                                    if let Some(expiry) = ignores.get(&normalized_path) {
-                                                        if std::time::Instant::now() < *expiry {
-                                                            // println!("RoBezy Watcher: Ignoring self-write on {}", normalized_path);
-                                                            continue;
-                                                        } else {
-                                                            // Expired
-                                                            ignores.remove(&normalized_path);
-                                                        }
-                                                    }
-                                                }
-                                                
-                                                // Heuristic ClassName from filename
-                                                // .server.lua -> Script
-                                                // .client.lua -> LocalScript
-                                                // .lua -> ModuleScript
-                                                let (name, class) = if relative.ends_with(".server.lua") {
-                                                     ("Script", "Script")
-                                                } else if relative.ends_with(".client.lua") {
-                                                     ("LocalScript", "LocalScript")
-                                                } else {
-                                                     ("ModuleScript", "ModuleScript")
-                                                };
-
-                                                let change = FileChange {
-                                                    change_type: "write".to_string(),
-                                                    path: normalized_path.clone(),
-                                                    content: Some(content),
-                                                    is_script: true, // Assuming all Lua are scripts
-                                                    guid: None, // We don't know the GUID from here easily
-                                                    class_name: Some(class.to_string()),
-                                                };
-                                                
-                                                // Push (Debounce check: peek last?)
-                                                let mut q = queue.lock().unwrap();
-                                                
-                                                // DEDUPLICATION: Remove any existing pending change for this exact path
-                                                q.retain(|c| c.path != normalized_path);
-                                                
-                                                q.push(change);
+                        EventKind::Modify(ModifyKind::Name(mode)) => {
+                            match mode {
+                                RenameMode::From => {
+                                    for p in paths {
+                                        if let Some((stale, _)) = pending_from.take() {
+                                            handle_remove(&folder_base, &stale, RemoveKind::Any, &queue, &ignore_paths, &fm);
+                                        }
+                                        pending_from = Some((p, std::time::Instant::now()));
+                                    }
+                                },
+                                RenameMode::To => {
+                                    for p in paths {
+                                        if let Some((from, _)) = pending_from.take() {
+                                            handle_rename(&folder_base, &from, &p, &queue, &ignore_paths, &fm);
+                                            last_pair = Some((from, p));
+                                        } else {
+                                            handle_created_tree(&folder_base, &p, &queue, &ignore_paths, &fm);
+                                        }
+                                    }
+                                },
+                                RenameMode::Both => {
+                                    if paths.len() == 2 {
+                                        let pair = (paths[0].clone(), paths[1].clone());
+                                        if last_pair.as_ref() == Some(&pair) {
+                                            // Already handled via From + To
+                                            last_pair = None;
+                                        } else {
+                                            if pending_from.as_ref().map(|(f, _)| f == &pair.0).unwrap_or(false) {
+                                                pending_from = None;
+                                            }
+                                            handle_rename(&folder_base, &pair.0, &pair.1, &queue, &ignore_paths, &fm);
+                                        }
+                                    }
+                                },
+                                _ => {
+                                    // Unpaired backend (macOS): the missing side is the old name
+                                    for p in paths {
+                                        if !p.exists() {
+                                            if let Some((stale, _)) = pending_from.take() {
+                                                handle_remove(&folder_base, &stale, RemoveKind::Any, &queue, &ignore_paths, &fm);
                                             }
+                                            pending_from = Some((p, std::time::Instant::now()));
+                                        } else if let Some((from, _)) = pending_from.take() {
+                                            handle_rename(&folder_base, &from, &p, &queue, &ignore_paths, &fm);
+                                        } else {
+                                            handle_created_tree(&folder_base, &p, &queue, &ignore_paths, &fm);
                                         }
                                     }
                                 }
                             }
                         },
+                        EventKind::Modify(_) | EventKind::Create(_) => {
+                            for p in paths {
+                                handle_write(&folder_base, &p, &queue, &ignore_paths, &fm);
+                            }
+                        },
+                        EventKind::Remove(remove_kind) => {
+                            for p in paths {
+                                handle_remove(&folder_base, &p, remove_kind, &queue, &ignore_paths, &fm);
+                            }
+                        },
                         _ => {}
                     }
                 },
                 Err(e) => eprintln!("Watch error: {:?}", e),
             }
+            fm.flush_sourcemap();
         }
     });
     
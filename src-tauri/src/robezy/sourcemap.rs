@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use serde::Serialize;
+use serde_json::Value;
+use crate::robezy::model::{self, FileKind, InstanceDescription};
+use crate::robezy::project::{ProjectMap, PROJECT_FILE};
+use crate::robezy::ignore::IgnoreRules;
+
+/// Rojo-style sourcemap kept in every bound folder, so luau-lsp (and agents) can resolve
+/// `require(game.ReplicatedStorage.X)` to files. Plain `.json`, so it is never synced.
+pub const SOURCEMAP_FILE: &str = "sourcemap.json";
+
+/// One synced file and the instance it belongs to.
+#[derive(Debug, Clone)]
+struct SourceFile {
+    instance: Vec<String>, // ["ReplicatedStorage", "Shared", "Util"], "~N" suffixes kept
+    class_name: Option<String>, // None for .meta.json that don't set one
+    children: Vec<InstanceDescription>, // Instances inside a .model.json
+}
+
+/// A node as luau-lsp reads it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceMapNode {
+    name: String,
+    class_name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    file_paths: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<SourceMapNode>,
+}
+
+#[derive(Default)]
+struct TreeNode {
+    class_name: Option<String>,
+    file_paths: Vec<String>,
+    children: BTreeMap<String, TreeNode>,
+}
+
+/// "Handler~2" -> "Handler" (the plugin's sibling number, see `sanitize_segment` in fs.rs).
+fn instance_name(segment: &str) -> &str {
+    match segment.rsplit_once('~') {
+        Some((name, n)) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => segment,
+    }
+}
+
+/// Files are tracked by relative path and only touched paths are re-read on `flush`,
+/// so a sync or snapshot costs the files it changed, not a walk of the whole folder.
+#[derive(Default)]
+pub struct SourceMap {
+    files: BTreeMap<String, SourceFile>, // Relative Path -> File
+    classes: HashMap<String, String>, // "Workspace.Map" -> "Model", from workspace snapshots
+    pending: HashSet<String>, // Relative paths touched since the last flush ("" = everything)
+    dirty: bool,
+    written: Option<String>, // Last JSON on disk
+}
+
+impl SourceMap {
+    /// A new map rescans the whole folder on its first flush.
+    pub fn new() -> Self {
+        let mut map = Self::default();
+        map.touch("");
+        map
+    }
+
+    /// Marks a relative file or folder path for re-reading on the next flush.
+    pub fn touch(&mut self, relative_path: &str) {
+        let key = relative_path.replace('\\', "/").trim_matches('/').to_string();
+        self.pending.insert(key);
+    }
+
+    /// Records the classes from a `workspace:fragment` ({ Path = "Workspace.Map", ClassName = "Model" }).
+    /// They name folders and services, which have no file telling their class.
+    pub fn apply_snapshot(&mut self, items: &[Value]) {
+        for item in items {
+            let (Some(path), Some(class_name)) = (
+                item.get("Path").and_then(|p| p.as_str()),
+                item.get("ClassName").and_then(|c| c.as_str()),
+            ) else {
+                continue;
+            };
+            if self.classes.get(path).map(|c| c.as_str()) != Some(class_name) {
+                self.classes.insert(path.to_string(), class_name.to_string());
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Re-reads the touched paths and rewrites `sourcemap.json` if the tree changed.
+    pub fn flush(&mut self, root_dir: &Path, project: Option<&ProjectMap>, ignore: &IgnoreRules) -> Result<(), String> {
+        for touched in std::mem::take(&mut self.pending) {
+            let prefix = format!("{}/", touched);
+            let before = self.files.len();
+            self.files.retain(|path, _| !(touched.is_empty() || *path == touched || path.starts_with(&prefix)));
+            self.dirty |= self.files.len() != before;
+
+            let full = root_dir.join(&touched);
+            if ignore.is_excluded(&touched, full.is_dir()) {
+                continue;
+            }
+            if full.is_dir() {
+                self.scan_dir(root_dir, &full, project, ignore);
+            } else if full.is_file() {
+                self.add_file(root_dir, &touched, project);
+            }
+        }
+        if !self.dirty {
+            return Ok(());
+        }
+        self.dirty = false;
+
+        let json = serde_json::to_string_pretty(&self.render(root_dir, project))
+            .map_err(|e| format!("Failed to serialize {}: {}", SOURCEMAP_FILE, e))?;
+        let path = root_dir.join(SOURCEMAP_FILE);
+        if self.written.is_none() {
+            self.written = std::fs::read_to_string(&path).ok();
+        }
+        if self.written.as_deref() == Some(json.as_str()) {
+            return Ok(());
+        }
+        crate::robezy::fs::write_atomic(&path, &json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        self.written = Some(json);
+        Ok(())
+    }
+
+    fn scan_dir(&mut self, root_dir: &Path, dir: &Path, project: Option<&ProjectMap>, ignore: &IgnoreRules) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name() == crate::robezy::fs::INTERNAL_DIR {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(root_dir) else { continue };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if ignore.is_excluded(&relative, path.is_dir()) {
+                continue;
+            }
+            if path.is_dir() {
+                self.scan_dir(root_dir, &path, project, ignore);
+            } else {
+                self.add_file(root_dir, &relative, project);
+            }
+        }
+    }
+
+    fn add_file(&mut self, root_dir: &Path, relative_path: &str, project: Option<&ProjectMap>) {
+        let Some(kind) = model::file_kind(relative_path) else { return };
+        if relative_path.starts_with(&format!("{}/", crate::robezy::fs::INTERNAL_DIR)) {
+            return;
+        }
+        let studio_path = match project {
+            Some(project) => match project.to_studio(relative_path) {
+                Some(path) => path,
+                None => return, // Not part of the project tree
+            },
+            None => relative_path.to_string(),
+        };
+        let mut parts: Vec<&str> = studio_path.split('/').collect();
+        let leaf = parts.pop().unwrap_or("");
+        if parts.is_empty() {
+            return; // Loose files in the folder root aren't instances
+        }
+        let stem = leaf.split('.').next().unwrap_or(leaf);
+        let mut instance: Vec<String> = parts.iter().map(|s| s.to_string()).collect();
+        if stem != "init" {
+            instance.push(stem.to_string());
+        }
+
+        let (class_name, children) = match kind {
+            FileKind::Script(class) | FileKind::Text(class) => (Some(class.to_string()), Vec::new()),
+            FileKind::Model | FileKind::Meta => {
+                let content = std::fs::read_to_string(root_dir.join(relative_path)).unwrap_or_default();
+                match model::parse(kind, relative_path, &content) {
+                    Ok(desc) => (desc.class_name, if kind == FileKind::Model { desc.children } else { Vec::new() }),
+                    Err(_) => (None, Vec::new()), // Broken files never reach Studio either
+                }
+            },
+        };
+        self.files.insert(relative_path.to_string(), SourceFile { instance, class_name, children });
+        self.dirty = true;
+    }
+
+    fn render(&self, root_dir: &Path, project: Option<&ProjectMap>) -> SourceMapNode {
+        let mut root = TreeNode::default();
+        for (path, file) in &self.files {
+            let mut node = &mut root;
+            for segment in &file.instance {
+                node = node.children.entry(segment.clone()).or_default();
+            }
+            // Scripts, text and models know their class; a .meta.json only sets it if nothing else does
+            if file.class_name.is_some() && (node.class_name.is_none() || !path.ends_with(".meta.json")) {
+                node.class_name = file.class_name.clone();
+            }
+            node.file_paths.push(path.clone());
+            for child in &file.children {
+                Self::insert_description(node, child);
+            }
+        }
+
+        let name = root_dir.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Project".to_string());
+        let mut out = self.render_node(&name, &mut Vec::new(), root, Some("DataModel"));
+        if project.is_some() {
+            out.file_paths.push(PROJECT_FILE.to_string());
+        }
+        out
+    }
+
+    fn insert_description(parent: &mut TreeNode, desc: &InstanceDescription) {
+        let Some(name) = desc.name.as_deref() else { return };
+        let node = parent.children.entry(name.to_string()).or_default();
+        node.class_name = desc.class_name.clone();
+        for child in &desc.children {
+            Self::insert_description(node, child);
+        }
+    }
+
+    fn render_node(&self, name: &str, path: &mut Vec<String>, node: TreeNode, class_name: Option<&str>) -> SourceMapNode {
+        let dotted = path.join(".");
+        let class_name = node.class_name.as_deref()
+            .or(class_name)
+            .or_else(|| self.classes.get(&dotted).map(|c| c.as_str()))
+            .unwrap_or(if path.len() == 1 { name } else { "Folder" }) // Services are their own class
+            .to_string();
+        let mut children = Vec::new();
+        for (segment, child) in node.children {
+            let child_name = instance_name(&segment).to_string();
+            path.push(child_name.clone());
+            children.push(self.render_node(&child_name, path, child, None));
+            path.pop();
+        }
+        SourceMapNode { name: name.to_string(), class_name, file_paths: node.file_paths, children }
+    }
+}
@@ -0,0 +1,146 @@
+use std::borrow::Cow;
+use crate::robezy::config::{LineEndings, NonUtf8, ProjectConfig};
+
+// Text crossing between disk and Studio goes through `decode` (disk -> Studio) and `encode`
+// (Studio -> disk) with the same rules, so a script hashes the same whichever side it came
+// from and whichever editor or OS saved it.
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16_LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16_BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// Drops a leading BOM character and, under `"lineEndings": "lf"`, turns CRLF into LF.
+pub fn normalize<'a>(text: &'a str, config: &ProjectConfig) -> Cow<'a, str> {
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+    match config.line_endings {
+        LineEndings::Lf if text.contains('\r') => Cow::Owned(text.replace("\r\n", "\n")),
+        _ => Cow::Borrowed(text),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16, config: &ProjectConfig) -> Result<String, String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err("is not valid UTF-16 (odd number of bytes)".to_string());
+    }
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| from_bytes([pair[0], pair[1]])).collect();
+    match String::from_utf16(&units) {
+        Ok(text) => Ok(text),
+        Err(_) if config.non_utf8 != NonUtf8::Skip => Ok(String::from_utf16_lossy(&units)),
+        Err(_) => Err("is not valid UTF-16".to_string()),
+    }
+}
+
+/// A file's bytes as the text Studio should get. UTF-8 and UTF-16 BOMs are honoured and dropped;
+/// anything else that isn't UTF-8 follows `"nonUtf8"` (an error under the default `"skip"`).
+pub fn decode(bytes: &[u8], config: &ProjectConfig) -> Result<String, String> {
+    let text = if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+        decode_utf8(rest, config)?
+    } else if let Some(rest) = bytes.strip_prefix(UTF16_LE_BOM) {
+        decode_utf16(rest, u16::from_le_bytes, config)?
+    } else if let Some(rest) = bytes.strip_prefix(UTF16_BE_BOM) {
+        decode_utf16(rest, u16::from_be_bytes, config)?
+    } else {
+        decode_utf8(bytes, config)?
+    };
+    Ok(match normalize(&text, config) {
+        Cow::Owned(normalized) => normalized,
+        Cow::Borrowed(_) => text,
+    })
+}
+
+fn decode_utf8(bytes: &[u8], config: &ProjectConfig) -> Result<String, String> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(text.to_string()),
+        Err(e) => match config.non_utf8 {
+            NonUtf8::Skip => Err(format!(
+                "is not valid UTF-8 (byte {}); set \"nonUtf8\" to \"latin1\" or \"lossy\" in robezy.json to sync it",
+                e.valid_up_to()
+            )),
+            NonUtf8::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+            NonUtf8::Lossy => Ok(String::from_utf8_lossy(bytes).into_owned()),
+        },
+    }
+}
+
+/// The bytes to write for text from Studio. Always UTF-8; under `"lineEndings": "preserve"`
+/// a BOM the file already had is kept, so round trips don't touch files Windows tools own.
+pub fn encode(text: &str, existing: Option<&[u8]>, config: &ProjectConfig) -> Vec<u8> {
+    let text = normalize(text, config);
+    let keep_bom = config.line_endings == LineEndings::Preserve
+        && existing.map(|bytes| bytes.starts_with(UTF8_BOM)).unwrap_or(false);
+    let mut out = Vec::with_capacity(text.len() + UTF8_BOM.len());
+    if keep_bom {
+        out.extend_from_slice(UTF8_BOM);
+    }
+    out.extend_from_slice(text.as_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(line_endings: LineEndings, non_utf8: NonUtf8) -> ProjectConfig {
+        ProjectConfig { line_endings, non_utf8, ..Default::default() }
+    }
+
+    fn utf16(text: &str, bom: &[u8], to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        let mut bytes = bom.to_vec();
+        bytes.extend(text.encode_utf16().flat_map(to_bytes));
+        bytes
+    }
+
+    #[test]
+    fn utf8_bom_is_dropped() {
+        let lf = ProjectConfig::default();
+        assert_eq!(decode(b"\xEF\xBB\xBFprint(1)\n", &lf).unwrap(), "print(1)\n");
+        assert_eq!(normalize("\u{FEFF}print(1)\n", &lf), "print(1)\n");
+    }
+
+    #[test]
+    fn utf16_with_a_bom_is_decoded() {
+        let lf = ProjectConfig::default();
+        assert_eq!(decode(&utf16("héllo\r\n", UTF16_LE_BOM, u16::to_le_bytes), &lf).unwrap(), "héllo\n");
+        assert_eq!(decode(&utf16("héllo\r\n", UTF16_BE_BOM, u16::to_be_bytes), &lf).unwrap(), "héllo\n");
+    }
+
+    #[test]
+    fn odd_length_utf16_is_an_error() {
+        let mut bytes = utf16("hi", UTF16_LE_BOM, u16::to_le_bytes);
+        bytes.push(b'!');
+        assert!(decode(&bytes, &ProjectConfig::default()).is_err());
+        assert!(decode(&bytes, &config(LineEndings::Lf, NonUtf8::Lossy)).is_err());
+    }
+
+    #[test]
+    fn unpaired_utf16_surrogates_follow_non_utf8() {
+        let bytes = [0xFF, 0xFE, 0x00, 0xD8, b'a', 0x00];
+        assert!(decode(&bytes, &ProjectConfig::default()).is_err());
+        assert_eq!(decode(&bytes, &config(LineEndings::Lf, NonUtf8::Lossy)).unwrap(), "\u{FFFD}a");
+    }
+
+    #[test]
+    fn lf_policy_turns_crlf_into_lf() {
+        let lf = ProjectConfig::default();
+        assert_eq!(decode(b"a\r\nb\r\n", &lf).unwrap(), "a\nb\n");
+        assert_eq!(encode("a\r\nb\r\n", None, &lf), b"a\nb\n");
+    }
+
+    #[test]
+    fn preserve_policy_keeps_line_endings_and_an_existing_bom() {
+        let preserve = config(LineEndings::Preserve, NonUtf8::Skip);
+        assert_eq!(decode(b"a\r\nb\n", &preserve).unwrap(), "a\r\nb\n");
+        assert_eq!(encode("a\r\n", Some(b"\xEF\xBB\xBFold"), &preserve), b"\xEF\xBB\xBFa\r\n");
+        assert_eq!(encode("a\r\n", Some(b"old"), &preserve), b"a\r\n");
+        // Under LF the BOM is not carried over
+        assert_eq!(encode("a\r\n", Some(b"\xEF\xBB\xBFold"), &ProjectConfig::default()), b"a\n");
+    }
+
+    #[test]
+    fn invalid_utf8_follows_non_utf8() {
+        let bytes = b"caf\xE9\n";
+        assert!(decode(bytes, &ProjectConfig::default()).is_err());
+        assert_eq!(decode(bytes, &config(LineEndings::Lf, NonUtf8::Latin1)).unwrap(), "café\n");
+        assert_eq!(decode(bytes, &config(LineEndings::Lf, NonUtf8::Lossy)).unwrap(), "caf\u{FFFD}\n");
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::robezy::session::ProjectFile;
+
+// Connect uploads too big for one request arrive as numbered, checksummed chunks. The plugin
+// announces a manifest first, so the backend knows when the upload is complete and can tell
+// the plugin which chunks are still missing after a failed request. A file too big for one
+// chunk is split into pieces that continue across chunks.
+
+/// Largest `data` one chunk may carry.
+pub const MAX_CHUNK_BYTES: usize = 1024 * 1024;
+/// Largest upload (all chunks together).
+const MAX_UPLOAD_BYTES: usize = 256 * 1024 * 1024;
+const MAX_CHUNKS: u32 = 4096;
+/// Request body limit for a chunk: JSON escaping can double `data`.
+pub const MAX_CHUNK_BODY: u64 = 2 * MAX_CHUNK_BYTES as u64 + 1024;
+/// Request body limit for a manifest (a few numbers and a session id).
+pub const MAX_MANIFEST_BODY: u64 = 4 * 1024;
+/// Uploads nobody touched for this long are dropped by the cleanup task.
+pub const UPLOAD_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Sent once before the chunks.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub session_id: String,
+    pub file_count: usize,
+    pub chunk_count: u32,
+    pub total_crc32: u32, // CRC32 of every chunk's `data`, concatenated in `seq` order
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Chunk {
+    pub seq: u32, // 1..=chunk_count
+    pub crc32: u32, // CRC32 of `data`
+    pub data: String, // JSON array of files (or pieces of them)
+}
+
+/// One entry of a chunk's `data`. A file too big for one chunk is sent as consecutive pieces with
+/// the same path: every piece but the last has `more: true`, and the next piece continues its content.
+#[derive(Debug, Deserialize)]
+struct FilePiece {
+    #[serde(flatten)]
+    file: ProjectFile,
+    #[serde(default)]
+    more: bool,
+}
+
+/// Reply to every upload request: what arrived and what to (re)send.
+#[derive(Debug, Serialize)]
+pub struct UploadStatus {
+    pub upload_id: String,
+    pub session_id: String,
+    pub received: u32,
+    pub chunk_count: u32,
+    pub missing: Vec<u32>,
+    pub complete: bool,
+}
+
+struct StoredChunk {
+    crc: crc32fast::Hasher,
+    bytes: usize,
+    pieces: Vec<FilePiece>,
+}
+
+/// The uploaded files, with split ones joined back together.
+fn assemble<'a>(chunks: impl Iterator<Item = &'a StoredChunk>) -> Result<Vec<ProjectFile>, String> {
+    let mut files = Vec::new();
+    let mut pending: Option<ProjectFile> = None;
+    for piece in chunks.flat_map(|c| c.pieces.iter()) {
+        let file = match pending.take() {
+            Some(mut file) if file.path == piece.file.path => {
+                file.content.push_str(&piece.file.content);
+                file
+            },
+            Some(file) => return Err(format!("{} is cut off", file.path)),
+            None => piece.file.clone(),
+        };
+        if piece.more {
+            pending = Some(file);
+        } else {
+            files.push(file);
+        }
+    }
+    match pending {
+        Some(file) => Err(format!("{} is cut off", file.path)),
+        None => Ok(files),
+    }
+}
+
+struct Upload {
+    manifest: Manifest,
+    chunks: BTreeMap<u32, StoredChunk>,
+    bytes: usize,
+    complete: bool,
+    last_activity: Instant,
+}
+
+impl Upload {
+    fn status(&self, upload_id: &str) -> UploadStatus {
+        UploadStatus {
+            upload_id: upload_id.to_string(),
+            session_id: self.manifest.session_id.clone(),
+            received: self.chunks.len() as u32,
+            chunk_count: self.manifest.chunk_count,
+            missing: (1..=self.manifest.chunk_count).filter(|seq| !self.chunks.contains_key(seq)).collect(),
+            complete: self.complete,
+        }
+    }
+
+    /// Checks the whole upload against its manifest once every chunk is in.
+    fn verify(&self) -> Result<(), String> {
+        let mut total = crc32fast::Hasher::new();
+        for chunk in self.chunks.values() {
+            total.combine(&chunk.crc);
+        }
+        let total = total.finalize();
+        if total != self.manifest.total_crc32 {
+            return Err(format!("total checksum is {} but the manifest says {}", total, self.manifest.total_crc32));
+        }
+        let files = assemble(self.chunks.values())?.len();
+        if files != self.manifest.file_count {
+            return Err(format!("{} files arrived but the manifest says {}", files, self.manifest.file_count));
+        }
+        Ok(())
+    }
+}
+
+/// Uploads in progress (and complete ones waiting for their connect), by upload id.
+#[derive(Default)]
+pub struct Uploads {
+    uploads: HashMap<String, Upload>,
+}
+
+impl Uploads {
+    /// Opens an upload. A session starting over replaces its previous upload.
+    pub fn start(&mut self, manifest: Manifest) -> Result<UploadStatus, String> {
+        if manifest.chunk_count == 0 || manifest.chunk_count > MAX_CHUNKS {
+            return Err(format!("chunk_count must be between 1 and {}", MAX_CHUNKS));
+        }
+        self.uploads.retain(|_, u| u.manifest.session_id != manifest.session_id);
+        let upload_id = Uuid::new_v4().to_string();
+        let upload = Upload { manifest, chunks: BTreeMap::new(), bytes: 0, complete: false, last_activity: Instant::now() };
+        let status = upload.status(&upload_id);
+        self.uploads.insert(upload_id, upload);
+        Ok(status)
+    }
+
+    /// Stores one chunk (resending a chunk replaces it). The chunk that completes the upload
+    /// also checks the manifest; on a mismatch the upload is dropped and has to start over.
+    pub fn add_chunk(&mut self, upload_id: &str, chunk: Chunk) -> Result<UploadStatus, String> {
+        let upload = self.uploads.get_mut(upload_id)
+            .ok_or_else(|| format!("unknown upload {} (expired?)", upload_id))?;
+        upload.last_activity = Instant::now();
+        if upload.complete {
+            return Ok(upload.status(upload_id));
+        }
+        if chunk.seq == 0 || chunk.seq > upload.manifest.chunk_count {
+            return Err(format!("chunk {} is outside 1..={}", chunk.seq, upload.manifest.chunk_count));
+        }
+        if chunk.data.len() > MAX_CHUNK_BYTES {
+            return Err(format!("chunk {} is larger than {} bytes", chunk.seq, MAX_CHUNK_BYTES));
+        }
+        let mut crc = crc32fast::Hasher::new();
+        crc.update(chunk.data.as_bytes());
+        let actual = crc.clone().finalize();
+        if actual != chunk.crc32 {
+            return Err(format!("chunk {} checksum is {} but {} was sent; resend it", chunk.seq, actual, chunk.crc32));
+        }
+        let replaced = upload.chunks.get(&chunk.seq).map(|c| c.bytes).unwrap_or(0);
+        if upload.bytes - replaced + chunk.data.len() > MAX_UPLOAD_BYTES {
+            return Err(format!("upload is larger than {} MB", MAX_UPLOAD_BYTES / (1024 * 1024)));
+        }
+        let pieces: Vec<FilePiece> = serde_json::from_str(&chunk.data)
+            .map_err(|e| format!("chunk {} is not a file list: {}", chunk.seq, e))?;
+
+        upload.bytes = upload.bytes - replaced + chunk.data.len();
+        upload.chunks.insert(chunk.seq, StoredChunk { crc, bytes: chunk.data.len(), pieces });
+        if upload.chunks.len() as u32 == upload.manifest.chunk_count {
+            if let Err(e) = upload.verify() {
+                self.uploads.remove(upload_id);
+                return Err(format!("upload {} doesn't match its manifest ({}); start a new upload", upload_id, e));
+            }
+            upload.complete = true;
+        }
+        Ok(upload.status(upload_id))
+    }
+
+    pub fn status(&self, upload_id: &str) -> Option<UploadStatus> {
+        self.uploads.get(upload_id).map(|u| u.status(upload_id))
+    }
+
+    /// Hands a complete upload's files to the connect that names it.
+    pub fn take(&mut self, upload_id: &str, session_id: &str) -> Result<Vec<ProjectFile>, String> {
+        let status = self.status(upload_id)
+            .ok_or_else(|| format!("unknown upload {} (expired?)", upload_id))?;
+        if status.session_id != session_id {
+            return Err(format!("upload {} belongs to another session", upload_id));
+        }
+        if !status.complete {
+            return Err(format!("upload {} is missing chunks {:?}", upload_id, status.missing));
+        }
+        let upload = self.uploads.remove(upload_id).unwrap();
+        assemble(upload.chunks.values())
+    }
+
+    /// The files of a session's complete upload, if its connect hasn't happened yet.
+    pub fn files(&self, session_id: &str) -> Option<Vec<ProjectFile>> {
+        self.uploads.values()
+            .find(|u| u.complete && u.manifest.session_id == session_id)
+            .and_then(|u| assemble(u.chunks.values()).ok())
+    }
+
+    /// Drops uploads idle for longer than `ttl`. Returns how many were dropped.
+    pub fn expire(&mut self, ttl: Duration) -> usize {
+        let before = self.uploads.len();
+        self.uploads.retain(|_, u| u.last_activity.elapsed() <= ttl);
+        before - self.uploads.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(chunks: &[Chunk], file_count: usize) -> Manifest {
+        let mut total = crc32fast::Hasher::new();
+        for chunk in chunks {
+            total.update(chunk.data.as_bytes());
+        }
+        Manifest { session_id: "session".to_string(), file_count, chunk_count: chunks.len() as u32, total_crc32: total.finalize() }
+    }
+
+    fn chunk(seq: u32, data: &str) -> Chunk {
+        Chunk { seq, crc32: crc32fast::hash(data.as_bytes()), data: data.to_string() }
+    }
+
+    fn piece(path: &str, content: &str, more: bool) -> String {
+        format!(r#"{{"path":"{}","content":"{}","more":{}}}"#, path, content, more)
+    }
+
+    /// Three chunks: A.lua, B.lua split across chunks 2 and 3, then C.lua
+    fn split_upload() -> Vec<Chunk> {
+        vec![
+            chunk(1, &format!("[{},{}]", piece("A.lua", "a", false), piece("B.lua", "b1", true))),
+            chunk(2, &format!("[{}]", piece("B.lua", "b2", true))),
+            chunk(3, &format!("[{},{}]", piece("B.lua", "b3", false), piece("C.lua", "c", false))),
+        ]
+    }
+
+    fn start(uploads: &mut Uploads, manifest: Manifest) -> String {
+        uploads.start(manifest).unwrap().upload_id
+    }
+
+    fn contents(files: &[ProjectFile]) -> Vec<(&str, &str)> {
+        files.iter().map(|f| (f.path.as_str(), f.content.as_str())).collect()
+    }
+
+    #[test]
+    fn split_files_are_joined_back() {
+        let chunks = split_upload();
+        let mut uploads = Uploads::default();
+        let id = start(&mut uploads, manifest(&chunks, 3));
+        for chunk in chunks {
+            uploads.add_chunk(&id, chunk).unwrap();
+        }
+        let files = uploads.take(&id, "session").unwrap();
+        assert_eq!(contents(&files), [("A.lua", "a"), ("B.lua", "b1b2b3"), ("C.lua", "c")]);
+    }
+
+    #[test]
+    fn chunks_can_arrive_out_of_order() {
+        let mut chunks = split_upload();
+        let mut uploads = Uploads::default();
+        let id = start(&mut uploads, manifest(&chunks, 3));
+        let last = chunks.pop().unwrap();
+        let first = chunks.remove(0);
+        let status = uploads.add_chunk(&id, last).unwrap();
+        assert_eq!((status.missing, status.complete), (vec![1, 2], false));
+        assert!(uploads.take(&id, "session").is_err());
+        uploads.add_chunk(&id, chunks.remove(0)).unwrap();
+        assert!(uploads.add_chunk(&id, first).unwrap().complete);
+        assert_eq!(contents(&uploads.take(&id, "session").unwrap())[1], ("B.lua", "b1b2b3"));
+    }
+
+    #[test]
+    fn chunk_with_a_wrong_checksum_is_rejected() {
+        let chunks = split_upload();
+        let mut uploads = Uploads::default();
+        let id = start(&mut uploads, manifest(&chunks, 3));
+        let mut broken = chunk(1, &chunks[0].data);
+        broken.crc32 ^= 1;
+        assert!(uploads.add_chunk(&id, broken).is_err());
+        assert_eq!(uploads.status(&id).unwrap().missing, [1, 2, 3]);
+    }
+
+    #[test]
+    fn total_checksum_mismatch_drops_the_upload() {
+        let chunks = split_upload();
+        let mut manifest = manifest(&chunks, 3);
+        manifest.total_crc32 ^= 1;
+        let mut uploads = Uploads::default();
+        let id = start(&mut uploads, manifest);
+        let mut results: Vec<_> = chunks.into_iter().map(|c| uploads.add_chunk(&id, c)).collect();
+        assert!(results.pop().unwrap().is_err());
+        assert!(uploads.status(&id).is_none());
+    }
+
+    #[test]
+    fn missing_piece_is_an_error() {
+        // B.lua says more follows, but the next piece is C.lua
+        let chunks = vec![
+            chunk(1, &format!("[{}]", piece("B.lua", "b1", true))),
+            chunk(2, &format!("[{}]", piece("C.lua", "c", false))),
+        ];
+        let mut uploads = Uploads::default();
+        let id = start(&mut uploads, manifest(&chunks, 2));
+        let mut results: Vec<_> = chunks.into_iter().map(|c| uploads.add_chunk(&id, c)).collect();
+        assert!(results.pop().unwrap().unwrap_err().contains("B.lua is cut off"));
+
+        // Or the upload ends in the middle of it
+        let chunks = vec![chunk(1, &format!("[{}]", piece("B.lua", "b1", true)))];
+        let id = start(&mut uploads, manifest(&chunks, 1));
+        assert!(uploads.add_chunk(&id, chunk(1, &chunks[0].data)).is_err());
+    }
+
+    #[test]
+    fn file_count_must_match_the_manifest() {
+        let chunks = split_upload();
+        let mut uploads = Uploads::default();
+        let id = start(&mut uploads, manifest(&chunks, 5));
+        let mut results: Vec<_> = chunks.into_iter().map(|c| uploads.add_chunk(&id, c)).collect();
+        assert!(results.pop().unwrap().is_err());
+    }
+
+    #[test]
+    fn chunk_count_is_limited() {
+        let mut uploads = Uploads::default();
+        let mut manifest = manifest(&split_upload(), 3);
+        manifest.chunk_count = 0;
+        assert!(uploads.start(manifest.clone()).is_err());
+        manifest.chunk_count = MAX_CHUNKS + 1;
+        assert!(uploads.start(manifest.clone()).is_err());
+        manifest.chunk_count = MAX_CHUNKS;
+        assert!(uploads.start(manifest).is_ok());
+    }
+
+    #[test]
+    fn oversized_chunks_and_uploads_are_rejected() {
+        let chunks = split_upload();
+        let mut uploads = Uploads::default();
+        let id = start(&mut uploads, manifest(&chunks, 3));
+        assert!(uploads.add_chunk(&id, chunk(4, "[]")).is_err());
+        assert!(uploads.add_chunk(&id, chunk(1, &"x".repeat(MAX_CHUNK_BYTES + 1))).is_err());
+
+        // Earlier chunks already used up all but a few bytes
+        uploads.uploads.get_mut(&id).unwrap().bytes = MAX_UPLOAD_BYTES - 10;
+        let error = uploads.add_chunk(&id, chunk(1, &chunks[0].data)).unwrap_err();
+        assert!(error.contains("larger than"));
+    }
+}
@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+// Every path that comes from Studio, a web client or an agent is joined to a project folder
+// through `resolve`, so a write or delete can never land outside of it.
+
+/// Names Windows reserves in every folder, with or without an extension ("CON.lua" too).
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+fn is_reserved(segment: &str) -> bool {
+    let stem = segment.split('.').next().unwrap_or(segment).trim_end_matches(' ');
+    RESERVED_NAMES.iter().any(|name| name.eq_ignore_ascii_case(stem))
+}
+
+/// Rejects paths that aren't plain relative paths: empty, absolute, with `..`, NUL bytes
+/// or reserved Windows names. Both `/` and `\` count as separators, whatever the platform.
+pub fn check_relative(relative_path: &Path) -> Result<(), String> {
+    let raw = relative_path.to_string_lossy();
+    let invalid = |reason: &str| Err(format!("Invalid path \"{}\": {}", raw.replace('\0', "\\0"), reason));
+
+    if raw.contains('\0') {
+        return invalid("contains a NUL byte");
+    }
+    let bytes = raw.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if raw.starts_with('/') || raw.starts_with('\\') || has_drive || relative_path.has_root() {
+        return invalid("absolute paths are not allowed");
+    }
+    let segments: Vec<&str> = raw.split(['/', '\\']).filter(|s| !s.is_empty() && *s != ".").collect();
+    if segments.is_empty() {
+        return invalid("path is empty");
+    }
+    for segment in segments {
+        if segment == ".." {
+            return invalid("\"..\" segments are not allowed");
+        }
+        if is_reserved(segment) {
+            return invalid(&format!("\"{}\" is a reserved name on Windows", segment));
+        }
+    }
+    Ok(())
+}
+
+/// Joins `relative_path` to `root_dir` after `check_relative`, then follows symlinks: the
+/// deepest part of the result that exists must still be inside `root_dir`.
+/// Returns the joined (not canonicalized) path, so it matches what the watcher reports.
+pub fn resolve(root_dir: &Path, relative_path: impl AsRef<Path>) -> Result<PathBuf, String> {
+    let relative_path = relative_path.as_ref();
+    check_relative(relative_path)?;
+
+    let real_root = root_dir.canonicalize()
+        .map_err(|e| format!("Folder {} is not accessible: {}", root_dir.display(), e))?;
+    let full = root_dir.join(relative_path);
+
+    let mut existing = full.as_path();
+    while std::fs::symlink_metadata(existing).is_err() {
+        existing = match existing.parent() {
+            Some(parent) => parent,
+            None => break,
+        };
+    }
+    let real = existing.canonicalize()
+        .map_err(|_| format!("Invalid path \"{}\": {} is a broken symlink", relative_path.display(), existing.display()))?;
+    if !real.starts_with(&real_root) {
+        return Err(format!("Invalid path \"{}\": resolves outside {} (through a symlink)", relative_path.display(), root_dir.display()));
+    }
+    Ok(full)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejected(path: &str) -> bool {
+        check_relative(Path::new(path)).is_err()
+    }
+
+    /// A project folder in the temp dir, removed when dropped
+    struct Folder(PathBuf);
+
+    impl Folder {
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("robezy-safe-path-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&root).unwrap();
+            Self(root)
+        }
+    }
+
+    impl Drop for Folder {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn plain_relative_paths_are_accepted() {
+        assert!(check_relative(Path::new("ServerScriptService/Foo.server.lua")).is_ok());
+        assert!(check_relative(Path::new("./Foo/Bar.lua")).is_ok());
+        assert!(check_relative(Path::new("Foo..bar.lua")).is_ok());
+        assert!(check_relative(Path::new("Console.lua")).is_ok());
+    }
+
+    #[test]
+    fn parent_segments_are_rejected() {
+        assert!(rejected(".."));
+        assert!(rejected("../Foo.lua"));
+        assert!(rejected("Foo/../../Bar.lua"));
+    }
+
+    #[test]
+    fn absolute_and_drive_paths_are_rejected() {
+        assert!(rejected("/etc/passwd"));
+        assert!(rejected("\\Windows\\win.ini"));
+        assert!(rejected("C:\\Windows\\win.ini"));
+        assert!(rejected("c:Foo.lua"));
+        assert!(rejected(""));
+        assert!(rejected("./"));
+    }
+
+    #[test]
+    fn nul_bytes_are_rejected() {
+        assert!(rejected("Foo\0.lua"));
+    }
+
+    #[test]
+    fn reserved_windows_names_are_rejected() {
+        assert!(rejected("CON"));
+        assert!(rejected("aux.lua"));
+        assert!(rejected("Foo/com1.server.lua"));
+        assert!(rejected("LPT9 .txt"));
+    }
+
+    #[test]
+    fn backslashes_are_separators() {
+        assert!(check_relative(Path::new("Foo\\Bar.lua")).is_ok());
+        assert!(rejected("Foo\\..\\..\\Bar.lua"));
+        assert!(rejected("Foo\\nul.lua"));
+    }
+
+    #[test]
+    fn resolve_joins_inside_the_root() {
+        let folder = Folder::new();
+        assert_eq!(resolve(&folder.0, "New/Foo.lua").unwrap(), folder.0.join("New/Foo.lua"));
+        assert!(resolve(&folder.0, "../Foo.lua").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_root_are_rejected() {
+        let folder = Folder::new();
+        let outside = Folder::new();
+        std::os::unix::fs::symlink(&outside.0, folder.0.join("Escape")).unwrap();
+        std::os::unix::fs::symlink(folder.0.join("Missing"), folder.0.join("Broken")).unwrap();
+        std::fs::create_dir(folder.0.join("Inside")).unwrap();
+        std::os::unix::fs::symlink(folder.0.join("Inside"), folder.0.join("Alias")).unwrap();
+
+        assert!(resolve(&folder.0, "Escape/Foo.lua").is_err());
+        assert!(resolve(&folder.0, "Escape").is_err());
+        assert!(resolve(&folder.0, "Broken/Foo.lua").is_err());
+        assert!(resolve(&folder.0, "Alias/Foo.lua").is_ok());
+    }
+}
@@ -55,15 +55,20 @@ pub async fn start_server(log_tx: broadcast::Sender<InternalBroadcast>, command_
     // GET /roblox/commands - Plugin polls this
     let queue_filter = warp::any().map(move || command_queue.clone());
     
+    // ?wait=<secs> holds the request open until a command arrives (long-poll)
+    #[derive(serde::Deserialize)]
+    struct CommandsQuery {
+        wait: Option<u64>,
+    }
+
     let commands_route = warp::path!("roblox" / "commands")
         .and(warp::get())
+        .and(warp::query::<CommandsQuery>())
         .and(queue_filter.clone())
-        .map(|queue: CommandQueue| {
-            let mut cmds = Vec::new();
-            if let Ok(mut q) = queue.lock() {
-                // Drain all pending commands to send to plugin
-                cmds = q.drain(..).collect();
-            }
+        .then(|query: CommandsQuery, queue: CommandQueue| async move {
+            // Drain all pending commands to send to plugin
+            let wait = std::time::Duration::from_secs(query.wait.unwrap_or(0).min(crate::server_ws::MAX_LONG_POLL_SECS));
+            let cmds = queue.drain_wait(wait).await;
             warp::reply::json(&cmds)
         });
 
@@ -50,6 +50,14 @@ enum ServerMessage {
     QueryResult { path: String, properties: serde_json::Value },
     #[serde(rename = "workspace:event")]
     WorkspaceEvent { content: serde_json::Value },
+    #[serde(rename = "script:class_changed", rename_all = "camelCase")]
+    ClassChanged { session_id: String, guid: String, old_path: String, new_path: String, old_class: Option<String>, new_class: Option<String> },
+    #[serde(rename = "conflict", rename_all = "camelCase")]
+    Conflict { session_id: String, path: String, conflict_path: String, guid: Option<String> },
+    #[serde(rename = "conflict:resolved", rename_all = "camelCase")]
+    ConflictResolved { session_id: String, path: String, resolution: String },
+    #[serde(rename = "session:reconciled", rename_all = "camelCase")]
+    Reconciled { session_id: String, plan: serde_json::Value },
 }
 
 // Internal broadcast type
@@ -62,6 +70,10 @@ pub enum InternalBroadcast {
     FileEvent { path: String, content: Option<String>, kind: String, source_id: Option<u64> },
     ProjectStop { source_id: Option<u64> },
     QueryResult { path: String, properties: serde_json::Value },
+    ClassChanged { session_id: String, guid: String, old_path: String, new_path: String, old_class: Option<String>, new_class: Option<String> },
+    Conflict { session_id: String, path: String, conflict_path: String, guid: Option<String> },
+    ConflictResolved { session_id: String, path: String, resolution: String },
+    Reconciled { session_id: String, plan: serde_json::Value }, // What a plugin connect found and did
 }
 
 // In a real app complexity, we'd inject this state or use a global.
@@ -87,10 +99,48 @@ pub struct RobloxCommand {
     pub params: serde_json::Value,
 }
 
-pub type CommandQueue = Arc<Mutex<Vec<RobloxCommand>>>;
+// Upper bound for `?wait=` long-polls (Roblox HttpService gives up after ~30s)
+pub const MAX_LONG_POLL_SECS: u64 = 25;
+
+/// Commands waiting for the CommandListener plugin. `ready` wakes long-polling requests.
+#[derive(Clone, Default)]
+pub struct CommandQueue {
+    commands: Arc<Mutex<Vec<RobloxCommand>>>,
+    ready: Arc<tokio::sync::Notify>,
+}
+
+impl CommandQueue {
+    pub fn push(&self, cmd: RobloxCommand) {
+        if let Ok(mut q) = self.commands.lock() {
+            q.push(cmd);
+        }
+        self.ready.notify_one();
+    }
+
+    pub fn drain(&self) -> Vec<RobloxCommand> {
+        match self.commands.lock() {
+            Ok(mut q) => q.drain(..).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Drains pending commands, waiting up to `wait` for one to arrive if there are none.
+    pub async fn drain_wait(&self, wait: std::time::Duration) -> Vec<RobloxCommand> {
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            let cmds = self.drain();
+            let now = tokio::time::Instant::now();
+            if !cmds.is_empty() || now >= deadline {
+                return cmds;
+            }
+            let _ = tokio::time::timeout(deadline - now, self.ready.notified()).await;
+        }
+    }
+}
 
 // Add SessionManager imports
 use crate::robezy::session::{SessionManager, FileChange};
+use crate::robezy::model::{file_kind, FileKind};
 
 pub async fn start_server(log_rx: broadcast::Sender<InternalBroadcast>, command_queue: CommandQueue, session_manager: Arc<Mutex<SessionManager>>) {
     let port = 3031;
@@ -135,12 +185,9 @@ pub async fn start_server(log_rx: broadcast::Sender<InternalBroadcast>, command_
                                          let filename = rel.file_name().unwrap_or_default().to_string_lossy().to_string();
                                          
                                          // Logic matches plugin_manager.rs expectations
-                                         if filename.ends_with(".server.lua") {
-                                             class_name = Some("Script".to_string());
-                                         } else if filename.ends_with(".client.lua") {
-                                             class_name = Some("LocalScript".to_string());
-                                         } else if filename.ends_with(".lua") {
-                                              class_name = Some("ModuleScript".to_string());
+                                         // .lua and .luau alike
+                                         if let Some(FileKind::Script(class)) = file_kind(&filename) {
+                                             class_name = Some(class.to_string());
                                          }
                                          
                                          // Plugin expects a "Roblox Path" (e.g. Workspace.Part)?
@@ -165,7 +212,8 @@ pub async fn start_server(log_rx: broadcast::Sender<InternalBroadcast>, command_
                                          change_type: "write".to_string(),
                                          class_name: class_name,
                                          guid: None,
-                                         is_script: true // Assume watcher only picks up scripts for now
+                                         is_script: true, // Assume watcher only picks up scripts for now
+                                         ..Default::default()
                                      });
                                  }
                              }
@@ -262,6 +310,18 @@ async fn handle_connection(
                      InternalBroadcast::WorkspaceEvent(content) => {
                          println!("WS: Received WorkspaceEvent broadcast"); // TRACE
                          Some(ServerMessage::WorkspaceEvent { content })
+                     },
+                     InternalBroadcast::ClassChanged { session_id, guid, old_path, new_path, old_class, new_class } => {
+                         Some(ServerMessage::ClassChanged { session_id, guid, old_path, new_path, old_class, new_class })
+                     },
+                     InternalBroadcast::Conflict { session_id, path, conflict_path, guid } => {
+                         Some(ServerMessage::Conflict { session_id, path, conflict_path, guid })
+                     },
+                     InternalBroadcast::ConflictResolved { session_id, path, resolution } => {
+                         Some(ServerMessage::ConflictResolved { session_id, path, resolution })
+                     },
+                     InternalBroadcast::Reconciled { session_id, plan } => {
+                         Some(ServerMessage::Reconciled { session_id, plan })
                      }
                  };
                  if let Some(s_msg) = server_msg {
@@ -299,7 +359,9 @@ async fn handle_connection(
                                     // ... Logic to create project ...
                                     if let Ok(path) = fs_manager::create_project(&unique_name) {
                                         for file in &files {
-                                            let _ = fs_manager::write_file(&unique_name, &file.path, &file.content);
+                                            if let Err(e) = fs_manager::write_file(&unique_name, &file.path, &file.content) {
+                                                eprintln!("Skipping {}: {}", file.path, e);
+                                            }
                                         }
                                         
                                         // BROADCAST
@@ -394,7 +456,10 @@ async fn handle_connection(
                                      
                                      let current_project = local_active_project.clone();
                                      if let Some(name) = current_project {
-                                        let _ = fs_manager::write_file(&name, &path, &content);
+                                        if let Err(e) = fs_manager::write_file(&name, &path, &content) {
+                                            let error = ServerMessage::Error { message: format!("Failed to write {}: {}", path, e) };
+                                            let _ = write.send(tokio_tungstenite::tungstenite::Message::Text(serde_json::to_string(&error).unwrap())).await;
+                                        }
                                      }
                                 },
                                 ClientMessage::FileDelete { path } => {
@@ -408,7 +473,10 @@ async fn handle_connection(
                                      
                                      let current_project = local_active_project.clone();
                                      if let Some(name) = current_project {
-                                        let _ = fs_manager::delete_file(&name, &path);
+                                        if let Err(e) = fs_manager::delete_file(&name, &path) {
+                                            let error = ServerMessage::Error { message: format!("Failed to delete {}: {}", path, e) };
+                                            let _ = write.send(tokio_tungstenite::tungstenite::Message::Text(serde_json::to_string(&error).unwrap())).await;
+                                        }
                                      }
                                 },
                                 ClientMessage::QueryInstance { path } => {
@@ -420,9 +488,7 @@ async fn handle_connection(
                                         params: serde_json::json!({ "path": path })
                                     };
                                     
-                                    if let Ok(mut queue) = command_queue.lock() {
-                                        queue.push(cmd);
-                                    }
+                                    command_queue.push(cmd);
                                 },
                             }
                         }
@@ -39,7 +39,7 @@ impl ProjectWatcher {
                                  if path.is_file() {
                                      // Check if it's a Lua file or relevant
                                      if let Some(ext) = path.extension() {
-                                         if ext == "lua" || ext == "json" { // simplified filter
+                                         if ext == "lua" || ext == "luau" || ext == "json" { // simplified filter
                                               if let Ok(content) = std::fs::read_to_string(&path) {
                                                    // Convert absolute path to relative for the web app
                                                    // TODO: proper error handling and path stripping